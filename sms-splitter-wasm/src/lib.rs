@@ -1,3 +1,4 @@
+use sms_splitter::splitter_error::SplitError;
 use wasm_bindgen::prelude::*;
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
@@ -8,17 +9,46 @@ static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 #[wasm_bindgen(typescript_custom_section)]
 const ISmsSplitter: &'static str = r#"
 export namespace ISmsSplitter {
+    export interface IPartOffsets {
+      byte_start: number;
+      byte_end: number;
+      char_start: number;
+      char_end: number;
+      utf16_start: number;
+      utf16_end: number;
+    }
+    export interface ICounts {
+      graphemes: number;
+      scalars: number;
+      utf16_units: number;
+      encoded_units: number;
+    }
+    export type NationalLanguage = "turkish" | "spanish" | "portuguese";
+    export type CharacterSet =
+      | "GSM"
+      | { gsm7_national: { locking: NationalLanguage | null, single: NationalLanguage | null } }
+      | "Unicode"
+      | "latin1"
+      | "binary";
     export interface ISplitSmsResult {
-      character_set: string;
-      parts: { content: string, length: number, bytes: number }[];
+      character_set: CharacterSet;
+      parts: { content: string, length: number, bytes: number, offsets: IPartOffsets, counts: ICounts }[];
       bytes: number;
       length: number;
+      counts: ICounts;
       remaining_in_part: number;
+      exceeds_max_parts: boolean;
+      efficiency_loss?: { hard_parts: number, extra_parts: number, unused_characters: number };
     }
 
     export interface ISplitterOptions {
         support_shift_tables: boolean;
         summary: boolean;
+        max_parts?: number;
+        max_parts_policy?: "error" | "truncate" | "flag";
+        split_mode?: "concatenated" | "independent";
+        part_numbering?: { pattern: string, position?: "prefix" | "suffix" };
+        encoding?: "auto" | "ucs2" | { gsm: "lookalike" | { char: string } };
     }
 }
 "#;
@@ -46,19 +76,23 @@ pub struct SmsSplitter {
 impl SmsSplitter {
 
     #[wasm_bindgen(constructor)]
-    pub fn new(options: Option<ISplitterOptions>) -> SmsSplitter {
+    pub fn new(options: Option<ISplitterOptions>) -> Result<SmsSplitter, JsValue> {
         let mut splitter_options = sms_splitter::splitter_options::SplitterOptions::default();
-        if options.is_some() {
-            let options: sms_splitter::splitter_options::SplitterOptions = serde_wasm_bindgen::from_value(options.unwrap().obj).unwrap();
-            splitter_options = options;
+        if let Some(options) = options {
+            splitter_options = serde_wasm_bindgen::from_value(options.obj).map_err(|e| {
+                JsValue::from_str(&SplitError::InvalidOptions(e.to_string()).to_string())
+            })?;
         }
-        SmsSplitter {
+        Ok(SmsSplitter {
             options: splitter_options,
-        }
+        })
     }
 
-    pub fn split(self,message: &str) -> ISplitSmsResult {
-        let result = sms_splitter::SplitSms::new(self.options).split(message.to_string());
-        ISplitSmsResult::from(parse(result.to_string().as_str()))
+    pub fn split(self,message: &str) -> Result<ISplitSmsResult, JsValue> {
+        let json = sms_splitter::SplitSms::new(self.options)
+            .try_split(message.to_string())
+            .and_then(|result| result.to_json())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(ISplitSmsResult::from(parse(&json)))
     }
 }
\ No newline at end of file
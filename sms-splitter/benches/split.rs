@@ -0,0 +1,33 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sms_splitter::SplitSms;
+
+fn messages() -> Vec<(&'static str, String)> {
+    vec![
+        (
+            "gsm_short",
+            String::from("Your verification code is 123456"),
+        ),
+        (
+            "gsm_long",
+            "The quick brown fox jumps over the lazy dog. ".repeat(20),
+        ),
+        ("unicode_long", "สวัสดีครับ ยินดีต้อนรับ ".repeat(20)),
+    ]
+}
+
+fn split(c: &mut Criterion) {
+    let splitter = SplitSms::default();
+    for (name, message) in messages() {
+        let mut group = c.benchmark_group(name);
+        group.bench_function("split", |b| {
+            b.iter(|| splitter.split(black_box(message.clone())))
+        });
+        group.bench_function("split_str", |b| {
+            b.iter(|| splitter.split_str(black_box(&message)).parts.len())
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, split);
+criterion_main!(benches);
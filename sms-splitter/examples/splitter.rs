@@ -1,3 +1,5 @@
+#![allow(clippy::unnecessary_to_owned)]
+
 use sms_splitter::SplitSms;
 
 fn main() {
@@ -0,0 +1,321 @@
+use std::{
+    format,
+    io::{Read, Write},
+    string::String,
+    vec,
+    vec::Vec,
+};
+
+use crate::pdu::{self, DataCoding, PduError, PduOptions};
+use crate::SplitSmsResult;
+
+const CTRL_Z: u8 = 0x1A;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtMode {
+    /// `AT+CMGF=0`, every part is sent as a hex SMS-SUBMIT PDU with a concatenation header.
+    Pdu,
+    /// `AT+CMGF=1`, every part is sent as an independent message; text mode has no UDH.
+    Text,
+}
+
+/// What the modem answers once a step has been written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtExpect {
+    /// Final result code `OK`.
+    Ok,
+    /// The `>` prompt asking for the message body.
+    Prompt,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtStep {
+    pub data: Vec<u8>,
+    pub expect: AtExpect,
+}
+
+impl AtStep {
+    /// A command line terminated by carriage return, answered with `OK`.
+    pub fn command(command: &str) -> AtStep {
+        AtStep {
+            data: format!("{}\r", command).into_bytes(),
+            expect: AtExpect::Ok,
+        }
+    }
+
+    /// An `AT+CMGS` line, answered with the `>` prompt.
+    pub fn send(command: &str) -> AtStep {
+        AtStep {
+            data: format!("{}\r", command).into_bytes(),
+            expect: AtExpect::Prompt,
+        }
+    }
+
+    /// A message body terminated by Ctrl-Z, answered with `+CMGS: <mr>` and `OK`.
+    pub fn body(mut data: Vec<u8>) -> AtStep {
+        data.push(CTRL_Z);
+        AtStep {
+            data,
+            expect: AtExpect::Ok,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AtOptions {
+    pub mode: AtMode,
+    pub pdu: PduOptions,
+}
+
+impl AtOptions {
+    pub fn new(mode: AtMode, destination: String, reference: u16) -> AtOptions {
+        AtOptions {
+            mode,
+            pdu: PduOptions::new(destination, reference),
+        }
+    }
+}
+
+/// Turns a split message into the command sequence that submits it with `AT+CMGS`.
+pub fn at_commands(result: &SplitSmsResult, options: &AtOptions) -> Result<Vec<AtStep>, PduError> {
+    match options.mode {
+        AtMode::Pdu => pdu_commands(result, &options.pdu),
+        AtMode::Text => text_commands(result, &options.pdu),
+    }
+}
+
+fn pdu_commands(result: &SplitSmsResult, options: &PduOptions) -> Result<Vec<AtStep>, PduError> {
+    let mut steps = vec![AtStep::command("AT+CMGF=0")];
+    for pdu in pdu::submit_pdus(result, options)? {
+        steps.push(AtStep::send(&format!("AT+CMGS={}", pdu.tpdu_length())));
+        steps.push(AtStep::body(pdu.to_hex().into_bytes()));
+    }
+    Ok(steps)
+}
+
+// The text goes through `AT+CSCS="UCS2"` as hex whatever the coding: raw GSM septets would put
+// NUL for '@', and ESC or Ctrl-Z for extension characters, on the serial line. The modem
+// converts the text to the coding set with `AT+CSMP`.
+fn text_commands(result: &SplitSmsResult, options: &PduOptions) -> Result<Vec<AtStep>, PduError> {
+    // validates the destination the same way PDU mode does
+    pdu::encode_address(&options.destination)?;
+    let coding = DataCoding::from_character_set(result.character_set)?;
+    let destination = pdu::to_hex(&pdu::encode_ucs2(&options.destination));
+    let mut steps = vec![
+        AtStep::command("AT+CMGF=1"),
+        AtStep::command("AT+CSCS=\"UCS2\""),
+        AtStep::command(&format!(
+            "AT+CSMP=17,{},0,{}",
+            options.validity_period,
+            coding.dcs()
+        )),
+    ];
+    for part in &result.parts {
+        if part.content.is_empty() && part.length > 0 {
+            return Err(PduError::MissingContent);
+        }
+        if coding == DataCoding::Gsm7 {
+            // the modem can only send what it can convert to GSM 7-bit
+            pdu::encode_gsm(&part.content)?;
+        }
+        steps.push(AtStep::send(&format!("AT+CMGS=\"{}\"", destination)));
+        steps.push(AtStep::body(
+            pdu::to_hex(&pdu::encode_ucs2(&part.content)).into_bytes(),
+        ));
+    }
+    Ok(steps)
+}
+
+#[derive(Debug)]
+pub enum AtError {
+    Io(std::io::Error),
+    /// The modem answered `ERROR`, `+CMS ERROR` or `+CME ERROR`; holds the response text.
+    Modem(String),
+}
+
+impl std::fmt::Display for AtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AtError::Io(e) => write!(f, "serial port error: {}", e),
+            AtError::Modem(response) => write!(f, "modem error: {}", response.trim()),
+        }
+    }
+}
+
+impl std::error::Error for AtError {}
+
+impl From<std::io::Error> for AtError {
+    fn from(e: std::io::Error) -> Self {
+        AtError::Io(e)
+    }
+}
+
+/// Drives a command sequence over anything that reads and writes like a serial port.
+pub struct AtSession<P: Read + Write> {
+    port: P,
+}
+
+impl<P: Read + Write> AtSession<P> {
+    pub fn new(port: P) -> AtSession<P> {
+        AtSession { port }
+    }
+
+    pub fn into_inner(self) -> P {
+        self.port
+    }
+
+    /// Writes every step and waits for its expected answer, returning the raw responses.
+    pub fn run(&mut self, steps: &[AtStep]) -> Result<Vec<String>, AtError> {
+        let mut responses = Vec::new();
+        for step in steps {
+            self.port.write_all(&step.data)?;
+            self.port.flush()?;
+            responses.push(self.read_response(step.expect)?);
+        }
+        Ok(responses)
+    }
+
+    fn read_response(&mut self, expect: AtExpect) -> Result<String, AtError> {
+        let mut response = String::new();
+        let mut byte = [0u8; 1];
+        loop {
+            if self.port.read(&mut byte)? == 0 {
+                return Err(AtError::Io(std::io::ErrorKind::UnexpectedEof.into()));
+            }
+            response.push(byte[0] as char);
+            if response.ends_with("ERROR\r\n")
+                || (response.contains("ERROR:") && response.ends_with("\r\n"))
+            {
+                return Err(AtError::Modem(response));
+            }
+            let done = match expect {
+                AtExpect::Ok => response.ends_with("OK\r\n"),
+                AtExpect::Prompt => response.ends_with("> "),
+            };
+            if done {
+                return Ok(response);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SplitSms;
+    use std::{collections::VecDeque, string::ToString};
+
+    // answers each line ending in CR or Ctrl-Z with the next scripted response
+    struct ScriptedPort {
+        script: VecDeque<&'static str>,
+        pending: VecDeque<u8>,
+        written: Vec<u8>,
+    }
+
+    impl ScriptedPort {
+        fn new(script: Vec<&'static str>) -> ScriptedPort {
+            ScriptedPort {
+                script: script.into(),
+                pending: VecDeque::new(),
+                written: Vec::new(),
+            }
+        }
+    }
+
+    impl Read for ScriptedPort {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let mut n = 0;
+            while n < buf.len() {
+                match self.pending.pop_front() {
+                    Some(b) => buf[n] = b,
+                    None => break,
+                }
+                n += 1;
+            }
+            Ok(n)
+        }
+    }
+
+    impl Write for ScriptedPort {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.written.extend_from_slice(buf);
+            if matches!(buf.last(), Some(b'\r') | Some(&CTRL_Z)) {
+                if let Some(response) = self.script.pop_front() {
+                    self.pending.extend(response.bytes());
+                }
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pdu_mode_sequence() {
+        let result = SplitSms::default().split(String::from("hellohello"));
+        let options = AtOptions::new(AtMode::Pdu, "+31641600986".to_string(), 0);
+        let steps = at_commands(&result, &options).unwrap();
+        let port = ScriptedPort::new(vec!["\r\nOK\r\n", "\r\n> ", "\r\n+CMGS: 12\r\n\r\nOK\r\n"]);
+        let mut session = AtSession::new(port);
+        let responses = session.run(&steps).unwrap();
+        assert_eq!(responses[2], "\r\n+CMGS: 12\r\n\r\nOK\r\n");
+        assert_eq!(
+            String::from_utf8(session.into_inner().written).unwrap(),
+            "AT+CMGF=0\rAT+CMGS=23\r0011000B911346610089F60000A70AE8329BFD4697D9EC37\u{1a}"
+        );
+    }
+
+    #[test]
+    fn pdu_mode_concatenated() {
+        let result = SplitSms::default().split("a".repeat(200));
+        let options = AtOptions::new(AtMode::Pdu, "+31641600986".to_string(), 1);
+        let steps = at_commands(&result, &options).unwrap();
+        assert_eq!(steps.len(), 5);
+        assert_eq!(steps[1], AtStep::send("AT+CMGS=154"));
+        assert_eq!(steps[3].expect, AtExpect::Prompt);
+    }
+
+    #[test]
+    fn text_mode_gsm() {
+        let result = SplitSms::default().split(String::from("a@b€"));
+        let options = AtOptions::new(AtMode::Text, "+31641600986".to_string(), 0);
+        let steps = at_commands(&result, &options).unwrap();
+        assert_eq!(steps[0], AtStep::command("AT+CMGF=1"));
+        assert_eq!(steps[1], AtStep::command("AT+CSCS=\"UCS2\""));
+        assert_eq!(steps[2], AtStep::command("AT+CSMP=17,167,0,0"));
+        assert_eq!(
+            steps[3],
+            AtStep::send("AT+CMGS=\"002B00330031003600340031003600300030003900380036\"")
+        );
+        assert_eq!(steps[4], AtStep::body(b"00610040006220AC".to_vec()));
+        // only the terminator is a control character
+        let body = &steps[4].data[..steps[4].data.len() - 1];
+        assert!(!body.iter().any(|b| [0x00, 0x1A, 0x1B].contains(b)));
+    }
+
+    #[test]
+    fn text_mode_ucs2() {
+        let result = SplitSms::default().split(String::from("สวัสดี"));
+        let options = AtOptions::new(AtMode::Text, "+66812".to_string(), 0);
+        let steps = at_commands(&result, &options).unwrap();
+        assert_eq!(steps[1], AtStep::command("AT+CSCS=\"UCS2\""));
+        assert_eq!(steps[2], AtStep::command("AT+CSMP=17,167,0,8"));
+        assert_eq!(
+            steps[3],
+            AtStep::send("AT+CMGS=\"002B00360036003800310032\"")
+        );
+        assert_eq!(steps[4], AtStep::body(b"0E2A0E270E310E2A0E140E35".to_vec()));
+    }
+
+    #[test]
+    fn modem_error() {
+        let result = SplitSms::default().split(String::from("hi"));
+        let options = AtOptions::new(AtMode::Pdu, "+31641600986".to_string(), 0);
+        let steps = at_commands(&result, &options).unwrap();
+        let port = ScriptedPort::new(vec!["\r\nOK\r\n", "\r\n> ", "\r\n+CMS ERROR: 500\r\n"]);
+        let error = AtSession::new(port).run(&steps).unwrap_err();
+        assert!(matches!(error, AtError::Modem(response) if response.contains("500")));
+    }
+}
@@ -0,0 +1,147 @@
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+
+use crate::{character_set::CharacterSet, SplitSms, SplitSmsResult};
+
+/// Totals over a batch of split messages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct BatchStats {
+    pub messages: usize,
+    pub parts: usize,
+    /// Messages by character set. Serialized by [`CharacterSet::as_str`], with all shift tables
+    /// counted under `gsm7_national`.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "by_name"))]
+    pub character_sets: BTreeMap<CharacterSet, usize>,
+    /// Messages by part count.
+    pub part_histogram: BTreeMap<usize, usize>,
+    pub exceeds_max_parts: usize,
+}
+
+// map keys have to be plain strings, and `Gsm7National` carries its tables
+#[cfg(feature = "serde")]
+fn by_name<S: Serializer>(
+    character_sets: &BTreeMap<CharacterSet, usize>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut names = BTreeMap::new();
+    for (character_set, messages) in character_sets.iter() {
+        *names.entry(character_set.as_str()).or_insert(0) += messages;
+    }
+    serializer.collect_map(names)
+}
+
+impl BatchStats {
+    pub fn add(&mut self, result: &SplitSmsResult) {
+        self.messages += 1;
+        self.parts += result.parts.len();
+        *self.character_sets.entry(result.character_set).or_default() += 1;
+        *self.part_histogram.entry(result.parts.len()).or_default() += 1;
+        self.exceeds_max_parts += usize::from(result.exceeds_max_parts);
+    }
+}
+
+impl<'a> FromIterator<&'a SplitSmsResult> for BatchStats {
+    fn from_iter<I: IntoIterator<Item = &'a SplitSmsResult>>(results: I) -> Self {
+        let mut stats = BatchStats::default();
+        for result in results {
+            stats.add(result);
+        }
+        stats
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    /// One result per message, in input order.
+    pub results: Vec<SplitSmsResult>,
+    pub stats: BatchStats,
+}
+
+impl BatchResult {
+    fn new(results: Vec<SplitSmsResult>) -> BatchResult {
+        BatchResult {
+            stats: results.iter().collect(),
+            results,
+        }
+    }
+}
+
+impl SplitSms {
+    /// Splits every message of a batch.
+    pub fn split_batch<I>(&self, messages: I) -> BatchResult
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        BatchResult::new(
+            messages
+                .into_iter()
+                .map(|message| self.split(message.into()))
+                .collect(),
+        )
+    }
+
+    /// Splits a slice of messages, in parallel with the `rayon` feature.
+    #[cfg(feature = "rayon")]
+    pub fn split_slice<S>(&self, messages: &[S]) -> BatchResult
+    where
+        S: AsRef<str> + Sync,
+    {
+        use rayon::prelude::*;
+
+        BatchResult::new(
+            messages
+                .par_iter()
+                .map(|message| self.split(String::from(message.as_ref())))
+                .collect(),
+        )
+    }
+
+    /// Splits a slice of messages, in parallel with the `rayon` feature.
+    #[cfg(not(feature = "rayon"))]
+    pub fn split_slice<S>(&self, messages: &[S]) -> BatchResult
+    where
+        S: AsRef<str> + Sync,
+    {
+        self.split_batch(messages.iter().map(|message| message.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn batch_stats() {
+        let messages = vec![
+            "a".repeat(10),
+            "a".repeat(200),
+            String::from("สวัสดี"),
+            "b".repeat(400),
+        ];
+        let batch = SplitSms::default().split_batch(messages.iter().map(String::as_str));
+        assert_eq!(batch.results.len(), 4);
+        assert_eq!(batch.results[1].parts.len(), 2);
+        assert_eq!(batch.stats.messages, 4);
+        assert_eq!(batch.stats.parts, 1 + 2 + 1 + 3);
+        assert_eq!(batch.stats.character_sets[&CharacterSet::Gsm7], 3);
+        assert_eq!(batch.stats.character_sets[&CharacterSet::Ucs2], 1);
+        assert_eq!(
+            batch.stats.part_histogram,
+            BTreeMap::from([(1, 2), (2, 1), (3, 1)])
+        );
+
+        #[cfg(feature = "serde_json")]
+        assert!(serde_json::to_string(&batch.stats)
+            .unwrap()
+            .contains(r#""character_sets":{"gsm7":3,"ucs2":1}"#));
+
+        let slice = SplitSms::default().split_slice(&messages);
+        assert_eq!(slice.stats, batch.stats);
+        assert_eq!(slice.results, batch.results);
+    }
+}
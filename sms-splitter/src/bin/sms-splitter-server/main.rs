@@ -0,0 +1,458 @@
+//! Serves the splitter over HTTP with JSON bodies, for services that cannot link the crate.
+//!
+//! - `POST /split`: the `SplitSmsResult` of `message`
+//! - `POST /validate`: whether `message` is GSM, its non-GSM characters and parts
+//! - `POST /encode`: the SMS-SUBMIT PDUs of `message` for `destination`
+//! - `POST /estimate`: the part counts of `template` for the `placeholders` constraints
+//! - `GET /health` and `GET /ready`
+//!
+//! Every `POST` body may carry `options`, as `SplitterOptions`, and `profile`, as
+//! `CarrierProfile`.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Read},
+    net::SocketAddr,
+    panic::{self, AssertUnwindSafe},
+    thread,
+};
+
+use clap::Parser;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use sms_splitter::{
+    carrier_profile::CarrierProfile,
+    character_set::CharacterSet,
+    pdu::{self, PduOptions},
+    splitter_options::{SplitterOptions, MAX_CONCATENATED_PARTS},
+    template::{Placeholder, Template, TemplateEstimate},
+    SplitSms, SplitSmsResult,
+};
+use tiny_http::{Header, Request, Response, Server};
+
+/// Serves the SMS splitter over HTTP.
+#[derive(Debug, Parser)]
+#[command(name = "sms-splitter-server", version)]
+struct Cli {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    bind: SocketAddr,
+    /// Largest request body accepted, in bytes.
+    #[arg(long, default_value_t = 64 * 1024)]
+    max_body: usize,
+    /// Requests handled at once.
+    #[arg(long, default_value_t = 4)]
+    threads: usize,
+}
+
+// the splitter a request asks for
+#[derive(Debug, Default, Deserialize)]
+struct Splitter {
+    #[serde(default)]
+    options: SplitterOptions,
+    #[serde(default)]
+    profile: CarrierProfile,
+}
+
+impl Splitter {
+    fn build(self) -> SplitSms {
+        SplitSms::with_profile(self.options, self.profile)
+    }
+
+    // the splitter, once its profile and options are known to be usable
+    fn checked(self) -> Result<SplitSms, Reply> {
+        let splitter = self.build();
+        splitter.validate().map_err(|e| Reply::error(422, e))?;
+        Ok(splitter)
+    }
+}
+
+// longest placeholder value estimated, whatever the profile allows: 255 parts of 160 septets
+const MAX_PLACEHOLDER_LENGTH: usize = MAX_CONCATENATED_PARTS * 160;
+
+#[derive(Debug, Deserialize)]
+struct MessageRequest {
+    message: String,
+    #[serde(flatten)]
+    splitter: Splitter,
+}
+
+#[derive(Debug, Deserialize)]
+struct EncodeRequest {
+    message: String,
+    destination: String,
+    #[serde(default)]
+    reference: u16,
+    validity_period: Option<u8>,
+    #[serde(flatten)]
+    splitter: Splitter,
+}
+
+#[derive(Debug, Deserialize)]
+struct EstimateRequest {
+    template: String,
+    placeholders: BTreeMap<String, Placeholder>,
+    #[serde(flatten)]
+    splitter: Splitter,
+}
+
+#[derive(Debug, Serialize)]
+struct Validation {
+    gsm: bool,
+    non_gsm: Vec<char>,
+    character_set: CharacterSet,
+    parts: usize,
+    /// Why the message cannot be split with the options, like too many parts.
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct EncodedPdu {
+    hex: String,
+    tpdu_length: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct Encoding {
+    character_set: CharacterSet,
+    pdus: Vec<EncodedPdu>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Reply {
+    status: u16,
+    body: String,
+}
+
+impl Reply {
+    fn json(status: u16, value: &impl Serialize) -> Reply {
+        match serde_json::to_string(value) {
+            Ok(body) => Reply { status, body },
+            Err(e) => Reply::error(500, e),
+        }
+    }
+
+    fn error(status: u16, error: impl ToString) -> Reply {
+        Reply {
+            status,
+            body: json!({ "error": error.to_string() }).to_string(),
+        }
+    }
+}
+
+fn body<T: DeserializeOwned>(body: &[u8]) -> Result<T, Reply> {
+    serde_json::from_slice(body).map_err(|e| Reply::error(400, e))
+}
+
+fn split(request: MessageRequest) -> Result<SplitSmsResult, Reply> {
+    request
+        .splitter
+        .build()
+        .try_split(request.message)
+        .map_err(|e| Reply::error(422, e))
+}
+
+fn validate(request: MessageRequest) -> Result<Validation, Reply> {
+    // the split below cannot count with an invalid profile
+    let splitter = request.splitter.checked()?;
+    let non_gsm = splitter.non_gsm_characters(&request.message);
+    let result = splitter.split(request.message.clone());
+    Ok(Validation {
+        gsm: non_gsm.is_empty(),
+        non_gsm,
+        character_set: result.character_set,
+        parts: result.parts.len(),
+        error: splitter
+            .try_split(request.message)
+            .err()
+            .map(|e| e.to_string()),
+    })
+}
+
+fn encode(request: EncodeRequest) -> Result<Encoding, Reply> {
+    let mut splitter = request.splitter;
+    // the PDUs carry the content of the parts
+    splitter.options.summary = false;
+    let udh = splitter.profile.udh;
+    let result = splitter
+        .build()
+        .try_split(request.message)
+        .map_err(|e| Reply::error(422, e))?;
+    let mut options = PduOptions::new(request.destination, request.reference);
+    options.udh = udh;
+    if let Some(validity_period) = request.validity_period {
+        options.validity_period = validity_period;
+    }
+    let pdus = pdu::submit_pdus(&result, &options).map_err(|e| Reply::error(422, e))?;
+    Ok(Encoding {
+        character_set: result.character_set,
+        pdus: pdus
+            .iter()
+            .map(|pdu| EncodedPdu {
+                hex: pdu.to_hex(),
+                tpdu_length: pdu.tpdu_length(),
+            })
+            .collect(),
+    })
+}
+
+fn estimate(request: EstimateRequest) -> Result<TemplateEstimate, Reply> {
+    let too_long = request
+        .placeholders
+        .iter()
+        .find(|(_, placeholder)| placeholder.max_length > MAX_PLACEHOLDER_LENGTH);
+    if let Some((name, _)) = too_long {
+        return Err(Reply::error(
+            422,
+            format!(
+                "placeholder {} has a max_length over {}",
+                name, MAX_PLACEHOLDER_LENGTH
+            ),
+        ));
+    }
+    request
+        .splitter
+        .checked()?
+        .estimate_template(&Template::parse(&request.template), &request.placeholders)
+        .map_err(|e| Reply::error(422, e))
+}
+
+fn reply<T: Serialize>(result: Result<T, Reply>) -> Reply {
+    result.map_or_else(|e| e, |value| Reply::json(200, &value))
+}
+
+fn route(method: &str, path: &str, bytes: &[u8]) -> Reply {
+    match (method, path) {
+        ("GET", "/health" | "/ready") => Reply::json(200, &json!({ "status": "ok" })),
+        ("POST", "/split") => reply(body(bytes).and_then(split)),
+        ("POST", "/validate") => reply(body(bytes).and_then(validate)),
+        ("POST", "/encode") => reply(body(bytes).and_then(encode)),
+        ("POST", "/estimate") => reply(body(bytes).and_then(estimate)),
+        (_, "/health" | "/ready" | "/split" | "/validate" | "/encode" | "/estimate") => {
+            Reply::error(405, "method not allowed")
+        }
+        _ => Reply::error(404, "not found"),
+    }
+}
+
+// reads at most one byte past `max_body`, so a body without a length cannot exhaust memory
+fn read_body(request: &mut Request, max_body: usize) -> Result<Vec<u8>, Reply> {
+    let too_large = || Reply::error(413, format!("body over {} bytes", max_body));
+    if request
+        .body_length()
+        .is_some_and(|length| length > max_body)
+    {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    request
+        .as_reader()
+        .take(max_body as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| Reply::error(400, e))?;
+    if bytes.len() > max_body {
+        return Err(too_large());
+    }
+    Ok(bytes)
+}
+
+fn handle(mut request: Request, max_body: usize) -> io::Result<()> {
+    let reply = match read_body(&mut request, max_body) {
+        Ok(bytes) => {
+            let path = request.url().split('?').next().unwrap_or_default();
+            // a panic fails the request, not the worker
+            panic::catch_unwind(AssertUnwindSafe(|| {
+                route(request.method().as_str(), path, &bytes)
+            }))
+            .unwrap_or_else(|_| Reply::error(500, "internal error"))
+        }
+        Err(reply) => reply,
+    };
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+    request.respond(
+        Response::from_string(reply.body)
+            .with_status_code(reply.status)
+            .with_header(content_type),
+    )
+}
+
+fn serve(server: &Server, max_body: usize) {
+    for request in server.incoming_requests() {
+        if let Err(e) = handle(request, max_body) {
+            eprintln!("sms-splitter-server: {}", e);
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let server = match Server::http(cli.bind) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("sms-splitter-server: cannot listen on {}: {}", cli.bind, e);
+            std::process::exit(1);
+        }
+    };
+    eprintln!("sms-splitter-server: listening on http://{}", cli.bind);
+    thread::scope(|scope| {
+        for _ in 0..cli.threads.max(1) {
+            scope.spawn(|| serve(&server, cli.max_body));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::{io::Write, net::TcpStream};
+
+    fn post(path: &str, body: Value) -> (u16, Value) {
+        let reply = route("POST", path, body.to_string().as_bytes());
+        (reply.status, serde_json::from_str(&reply.body).unwrap())
+    }
+
+    #[test]
+    fn split_and_validate() {
+        let (status, result) = post("/split", json!({ "message": "Hello World!" }));
+        assert_eq!(status, 200);
+        assert_eq!(result["character_set"], "GSM");
+        assert_eq!(result["parts"][0]["content"], "Hello World!");
+
+        let options = json!({ "support_shift_tables": false, "summary": true, "max_parts": 1 });
+        let message = "a".repeat(200) + "\u{2019}";
+        let (status, result) = post("/split", json!({ "message": message, "options": options }));
+        assert_eq!(status, 422);
+        assert_eq!(result["error"], "message needs 3 parts, the limit is 1");
+
+        let (status, validation) = post(
+            "/validate",
+            json!({ "message": message, "options": options }),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(validation["gsm"], false);
+        assert_eq!(validation["non_gsm"], json!(["\u{2019}"]));
+        assert_eq!(validation["character_set"], "Unicode");
+        assert_eq!(validation["parts"], 3);
+        assert_eq!(validation["error"], "message needs 3 parts, the limit is 1");
+
+        let mut profile = serde_json::to_value(CarrierProfile::gsm()).unwrap();
+        profile["gsm"]["multi"] = json!(0);
+        let request = json!({ "message": "a".repeat(300), "profile": profile });
+        let (status, result) = post("/validate", request);
+        assert_eq!(status, 422);
+        assert!(result["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid options"));
+    }
+
+    #[test]
+    fn encode_and_estimate() {
+        let (status, encoding) = post(
+            "/encode",
+            json!({ "message": "hellohello", "destination": "+1234567890" }),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(encoding["character_set"], "GSM");
+        assert_eq!(encoding["pdus"][0]["tpdu_length"], 22);
+        assert_eq!(
+            encoding["pdus"][0]["hex"],
+            "0011000A9121436587090000A70AE8329BFD4697D9EC37"
+        );
+        let (status, _) = post("/encode", json!({ "message": "hi", "destination": "12a" }));
+        assert_eq!(status, 422);
+
+        let (status, estimate) = post(
+            "/estimate",
+            json!({
+                "template": "Hi {name}, your code is {code}",
+                "placeholders": {
+                    "name": { "max_length": 20, "characters": "unicode" },
+                    "code": { "min_length": 6, "max_length": 6, "characters": "digits" },
+                },
+            }),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(estimate["minimum"]["character_set"], "GSM");
+        assert_eq!(estimate["worst_case"]["character_set"], "Unicode");
+        let (status, _) = post(
+            "/estimate",
+            json!({ "template": "{x}", "placeholders": {} }),
+        );
+        assert_eq!(status, 422);
+
+        // a small body asking for a huge worst case
+        let placeholders = json!({ "x": { "max_length": u64::MAX } });
+        let (status, result) = post(
+            "/estimate",
+            json!({ "template": "{x}", "placeholders": placeholders }),
+        );
+        assert_eq!(status, 422);
+        assert_eq!(result["error"], "placeholder x has a max_length over 40800");
+        let mut profile = serde_json::to_value(CarrierProfile::gsm()).unwrap();
+        profile["max_parts"] = json!(0);
+        let placeholders = json!({ "x": { "max_length": 10 } });
+        let (status, _) = post(
+            "/estimate",
+            json!({ "template": "{x}", "placeholders": placeholders, "profile": profile }),
+        );
+        assert_eq!(status, 422);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(route("GET", "/health", b"").status, 200);
+        assert_eq!(route("GET", "/split", b"").status, 405);
+        assert_eq!(route("POST", "/missing", b"{}").status, 404);
+        let reply = route("POST", "/split", b"{\"text\":\"hi\"}");
+        assert_eq!(reply.status, 400);
+        assert!(reply.body.contains("missing field `message`"));
+    }
+
+    // a request over a real connection, answered by `serve`
+    fn request(address: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn localhost() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let address = server.server_addr().to_ip().unwrap();
+        thread::scope(|scope| {
+            scope.spawn(|| serve(&server, 32));
+            let body = "{\"message\":\"Hello\"}";
+            let response = request(
+                address,
+                &format!(
+                    "POST /split HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+                     Content-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                ),
+            );
+            assert!(response.starts_with("HTTP/1.1 200"));
+            assert!(response.contains("Content-Type: application/json"));
+            assert!(response.ends_with("\"exceeds_max_parts\":false}"));
+
+            let body = format!("{{\"message\":\"{}\"}}", "a".repeat(40));
+            let response = request(
+                address,
+                &format!(
+                    "POST /split HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+                     Content-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                ),
+            );
+            assert!(response.starts_with("HTTP/1.1 413"));
+            assert!(response.ends_with("{\"error\":\"body over 32 bytes\"}"));
+            server.unblock();
+        });
+    }
+}
@@ -0,0 +1,226 @@
+//! Splits the message of every row of a CSV file or JSON Lines stream, one row at a time, and
+//! writes the rows back with the split appended.
+
+use std::{
+    error::Error,
+    io::{BufRead, Write},
+    path::Path,
+};
+
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+use sms_splitter::{batch::BatchStats, SplitSms, SplitSmsResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Format {
+    Csv,
+    Jsonl,
+}
+
+impl Format {
+    // JSON Lines for .jsonl and .ndjson files, CSV otherwise
+    pub(crate) fn of(path: &Path) -> Format {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("jsonl" | "ndjson") => Format::Jsonl,
+            _ => Format::Csv,
+        }
+    }
+}
+
+// appended to every row
+const COLUMNS: [&str; 5] = ["encoding", "parts", "length", "bytes", "non_gsm"];
+
+/// Totals of a batch, on top of the [`BatchStats`] of the library.
+#[derive(Debug, Default)]
+pub(crate) struct Summary {
+    pub stats: BatchStats,
+    /// Messages with characters outside the GSM alphabet.
+    pub non_gsm: usize,
+}
+
+impl Summary {
+    pub(crate) fn write(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let stats = &self.stats;
+        writeln!(out, "messages             {}", stats.messages)?;
+        writeln!(out, "parts                {}", stats.parts)?;
+        for (character_set, messages) in stats.character_sets.iter() {
+            writeln!(out, "{:<20} {}", character_set.as_str(), messages)?;
+        }
+        for (parts, messages) in stats.part_histogram.iter() {
+            let label = format!("{} part{}", parts, if *parts == 1 { "" } else { "s" });
+            writeln!(out, "{:<20} {}", label, messages)?;
+        }
+        writeln!(out, "non-GSM              {}", self.non_gsm)?;
+        writeln!(out, "exceeds max parts    {}", stats.exceeds_max_parts)
+    }
+}
+
+pub(crate) struct Batch<'a> {
+    pub splitter: &'a SplitSms,
+    /// Column or field holding the message.
+    pub column: &'a str,
+}
+
+impl Batch<'_> {
+    pub(crate) fn run(
+        &self,
+        format: Format,
+        input: impl BufRead,
+        out: impl Write,
+    ) -> Result<Summary, Box<dyn Error>> {
+        let mut summary = Summary::default();
+        match format {
+            Format::Csv => self.csv(input, out, &mut summary)?,
+            Format::Jsonl => self.jsonl(input, out, &mut summary)?,
+        }
+        Ok(summary)
+    }
+
+    // the split of a row and the characters of its message outside the GSM alphabet
+    fn split(&self, message: &str, summary: &mut Summary) -> (SplitSmsResult, String) {
+        let result = self.splitter.split(String::from(message));
+        let non_gsm: String = self
+            .splitter
+            .non_gsm_characters(message)
+            .into_iter()
+            .collect();
+        summary.stats.add(&result);
+        summary.non_gsm += usize::from(!non_gsm.is_empty());
+        (result, non_gsm)
+    }
+
+    fn csv(
+        &self,
+        input: impl BufRead,
+        out: impl Write,
+        summary: &mut Summary,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut reader = csv::Reader::from_reader(input);
+        let mut writer = csv::Writer::from_writer(out);
+        let headers = reader.headers()?.clone();
+        let column = headers
+            .iter()
+            .position(|header| header == self.column)
+            .ok_or_else(|| format!("no {} column", self.column))?;
+        writer.write_record(headers.iter().chain(COLUMNS))?;
+        for record in reader.records() {
+            let record = record?;
+            let (result, non_gsm) = self.split(&record[column], summary);
+            let columns = [
+                result.character_set.to_string(),
+                result.parts.len().to_string(),
+                result.length.to_string(),
+                result.bytes.to_string(),
+                non_gsm,
+            ];
+            writer.write_record(record.iter().chain(columns.iter().map(String::as_str)))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn jsonl(
+        &self,
+        input: impl BufRead,
+        mut out: impl Write,
+        summary: &mut Summary,
+    ) -> Result<(), Box<dyn Error>> {
+        for (i, line) in input.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut row: Map<String, Value> =
+                serde_json::from_str(&line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+            let message = row
+                .get(self.column)
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("line {}: no {} string", i + 1, self.column))?;
+            let (result, non_gsm) = self.split(message, summary);
+            let fields = [
+                Value::from(result.character_set.as_str()),
+                Value::from(result.parts.len()),
+                Value::from(result.length),
+                Value::from(result.bytes),
+                Value::from(non_gsm),
+            ];
+            for (name, value) in COLUMNS.into_iter().zip(fields) {
+                row.insert(String::from(name), value);
+            }
+            writeln!(out, "{}", serde_json::to_string(&row)?)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sms_splitter::character_set::CharacterSet;
+
+    fn run(format: Format, input: &str) -> Result<(String, Summary), Box<dyn Error>> {
+        let splitter = SplitSms::default();
+        let batch = Batch {
+            splitter: &splitter,
+            column: "message",
+        };
+        let mut out = Vec::new();
+        let summary = batch.run(format, input.as_bytes(), &mut out)?;
+        Ok((String::from_utf8(out)?, summary))
+    }
+
+    #[test]
+    fn csv_columns() {
+        let input = format!(
+            "id,message\n1,Hello\n2,\"it\u{2019}s, {}\"\n",
+            "a".repeat(200)
+        );
+        let (out, summary) = run(Format::Csv, &input).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "id,message,encoding,parts,length,bytes,non_gsm");
+        assert_eq!(lines[1], "1,Hello,gsm7,1,5,5,");
+        assert!(lines[2].ends_with(",ucs2,4,206,412,\u{2019}"));
+        assert_eq!(summary.stats.messages, 2);
+        assert_eq!(summary.stats.parts, 5);
+        assert_eq!(summary.stats.character_sets[&CharacterSet::Ucs2], 1);
+        assert_eq!(summary.non_gsm, 1);
+
+        let error = run(Format::Csv, "id,text\n1,Hello\n").unwrap_err();
+        assert_eq!(error.to_string(), "no message column");
+    }
+
+    #[test]
+    fn jsonl_fields() {
+        let input = "{\"message\":\"Hello\",\"to\":\"+4412345\"}\n\n{\"message\":\"\u{1F600}\"}\n";
+        let (out, summary) = run(Format::Jsonl, input).unwrap();
+        let rows: Vec<Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["to"], "+4412345");
+        assert_eq!(rows[0]["encoding"], "gsm7");
+        assert_eq!(rows[0]["parts"], 1);
+        assert_eq!(rows[1]["bytes"], 4);
+        assert_eq!(rows[1]["non_gsm"], "\u{1F600}");
+        assert_eq!(summary.stats.messages, 2);
+
+        let error = run(Format::Jsonl, "{\"text\":\"Hello\"}\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: no message string");
+    }
+
+    #[test]
+    fn summary() {
+        let (_, summary) = run(Format::Jsonl, "{\"message\":\"Hello\"}\n").unwrap();
+        let mut out = Vec::new();
+        summary.write(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("messages             1\n"));
+        assert!(out.contains("gsm7                 1\n"));
+        assert!(out.contains("1 part               1\n"));
+        assert!(out.ends_with("exceeds max parts    0\n"));
+        assert_eq!(Format::of(Path::new("variants.jsonl")), Format::Jsonl);
+        assert_eq!(Format::of(Path::new("variants.csv")), Format::Csv);
+    }
+}
@@ -0,0 +1,320 @@
+//! Splits a message from the command line and shows its parts, to see why a message takes the
+//! parts it does without writing code.
+
+mod batch;
+
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use batch::{Batch, Format};
+use clap::{Args, Parser};
+use serde::de::DeserializeOwned;
+use sms_splitter::{
+    part_numbering::{NumberingPosition, PartNumbering},
+    splitter_options::{Encoding, MaxPartsPolicy, Replacement, SplitMode, SplitterOptions},
+    SplitSms, SplitSmsResult,
+};
+
+// the message goes out in Unicode and --require-gsm was given
+const EXIT_ENCODING: u8 = 3;
+// the message needs more parts than --max-parts allows
+const EXIT_PARTS: u8 = 4;
+
+/// Splits an SMS message into parts.
+///
+/// Exits with 3 when --require-gsm is given and the message goes out in Unicode, and with 4 when
+/// it needs more parts than --max-parts. In batch mode, when any row does.
+#[derive(Debug, Parser)]
+#[command(name = "sms-splitter", version)]
+struct Cli {
+    /// The message, read from --file or stdin when missing.
+    text: Option<String>,
+    /// File to read the message from, `-` for stdin. A final line break is dropped.
+    #[arg(short, long, conflicts_with = "text")]
+    file: Option<PathBuf>,
+    /// Print the result as JSON instead of a table.
+    #[arg(long)]
+    json: bool,
+    /// Fail when the message goes out in Unicode, as characters outside the GSM alphabet make it
+    /// unless --encoding gsm replaces them.
+    #[arg(long)]
+    require_gsm: bool,
+    /// CSV or JSON Lines file to split the messages of, `-` for stdin. Rows are written to stdout
+    /// with encoding, parts, length, bytes and non_gsm appended, and totals to stderr.
+    #[arg(long, conflicts_with_all = ["text", "file", "json"])]
+    batch: Option<PathBuf>,
+    /// Format of the batch, from its extension by default: csv, or jsonl for .jsonl and .ndjson.
+    #[arg(long, requires = "batch")]
+    format: Option<Format>,
+    /// Column or field of the batch holding the message [default: message]
+    #[arg(long, requires = "batch")]
+    column: Option<String>,
+    #[command(flatten)]
+    options: OptionArgs,
+}
+
+// flags of SplitterOptions
+#[derive(Debug, Args)]
+struct OptionArgs {
+    /// Accept characters of the national language shift tables as GSM.
+    #[arg(long)]
+    shift_tables: bool,
+    /// Count the parts without their content.
+    #[arg(long)]
+    summary: bool,
+    /// Most parts the message may take.
+    #[arg(long)]
+    max_parts: Option<usize>,
+    /// What to do past --max-parts: error, truncate or flag.
+    #[arg(long, value_parser = lowercase::<MaxPartsPolicy>)]
+    max_parts_policy: Option<MaxPartsPolicy>,
+    /// concatenated, or independent for messages cut at words without a concatenation header.
+    #[arg(long, value_parser = lowercase::<SplitMode>)]
+    split_mode: Option<SplitMode>,
+    /// Counter written into every part, like "({part}/{total}) ".
+    #[arg(long)]
+    numbering: Option<String>,
+    /// Where the counter goes: prefix or suffix.
+    #[arg(long, requires = "numbering", value_parser = lowercase::<NumberingPosition>)]
+    numbering_position: Option<NumberingPosition>,
+    /// auto, gsm or ucs2. Forced GSM replaces other characters by a lookalike.
+    #[arg(long, value_parser = ["auto", "gsm", "ucs2"])]
+    encoding: Option<String>,
+    /// Replace characters outside GSM by this one instead, forcing GSM.
+    #[arg(long, conflicts_with = "encoding")]
+    replacement: Option<char>,
+}
+
+// option values are spelled as in the JSON of the options
+fn lowercase<T: DeserializeOwned>(value: &str) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::from(value)).map_err(|e| e.to_string())
+}
+
+impl OptionArgs {
+    fn splitter_options(&self) -> SplitterOptions {
+        let encoding = match (self.encoding.as_deref(), self.replacement) {
+            (_, Some(replacement)) => Encoding::Gsm(Replacement::Char(replacement)),
+            (Some("gsm"), None) => Encoding::Gsm(Replacement::Lookalike),
+            (Some("ucs2"), None) => Encoding::Ucs2,
+            _ => Encoding::Auto,
+        };
+        SplitterOptions {
+            max_parts: self.max_parts,
+            max_parts_policy: self.max_parts_policy.unwrap_or_default(),
+            split_mode: self.split_mode.unwrap_or_default(),
+            part_numbering: self.numbering.as_ref().map(|pattern| {
+                PartNumbering::new(pattern.clone(), self.numbering_position.unwrap_or_default())
+            }),
+            encoding,
+            ..SplitterOptions::new(self.shift_tables, self.summary)
+        }
+    }
+}
+
+impl Cli {
+    fn message(&self) -> io::Result<String> {
+        if let Some(text) = &self.text {
+            return Ok(text.clone());
+        }
+        let mut message = match &self.file {
+            Some(path) if path.as_os_str() != "-" => fs::read_to_string(path)?,
+            _ => {
+                let mut message = String::new();
+                io::stdin().read_to_string(&mut message)?;
+                message
+            }
+        };
+        if message.ends_with('\n') {
+            message.pop();
+            if message.ends_with('\r') {
+                message.pop();
+            }
+        }
+        Ok(message)
+    }
+}
+
+fn write_table(out: &mut impl Write, result: &SplitSmsResult, non_gsm: &[char]) -> io::Result<()> {
+    writeln!(out, "encoding   {}", result.character_set)?;
+    writeln!(out, "parts      {}", result.parts.len())?;
+    writeln!(out, "length     {}", result.length)?;
+    writeln!(out, "bytes      {}", result.bytes)?;
+    writeln!(out, "remaining  {}", result.remaining_in_part)?;
+    if !non_gsm.is_empty() {
+        let chars: Vec<String> = non_gsm
+            .iter()
+            .map(|c| format!("{} U+{:04X}", c.escape_debug(), *c as u32))
+            .collect();
+        writeln!(out, "non-GSM    {}", chars.join(", "))?;
+    }
+    if result.exceeds_max_parts {
+        writeln!(out, "exceeds max parts")?;
+    }
+    writeln!(out)?;
+    writeln!(out, "part  length  bytes  content")?;
+    for (i, part) in result.parts.iter().enumerate() {
+        writeln!(
+            out,
+            "{:<5} {:<7} {:<6} {}",
+            i + 1,
+            part.length,
+            part.bytes,
+            part.content.escape_debug()
+        )?;
+    }
+    Ok(())
+}
+
+fn exit_code(cli: &Cli, unicode: bool, exceeds_max_parts: bool) -> ExitCode {
+    if cli.require_gsm && unicode {
+        return ExitCode::from(EXIT_ENCODING);
+    }
+    if exceeds_max_parts {
+        return ExitCode::from(EXIT_PARTS);
+    }
+    ExitCode::SUCCESS
+}
+
+fn run_batch(cli: &Cli, path: &PathBuf) -> Result<ExitCode, Box<dyn Error>> {
+    let input: Box<dyn BufRead> = if path.as_os_str() == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(path)?))
+    };
+    // the rows only get the counts, so the parts need no content
+    let options = SplitterOptions {
+        summary: true,
+        ..cli.options.splitter_options()
+    };
+    let splitter = SplitSms::new(options);
+    splitter.validate()?;
+    let batch = Batch {
+        splitter: &splitter,
+        column: cli.column.as_deref().unwrap_or("message"),
+    };
+    let format = cli.format.unwrap_or_else(|| Format::of(path));
+    let summary = batch.run(format, input, BufWriter::new(io::stdout().lock()))?;
+    summary.write(&mut io::stderr().lock())?;
+    let mut character_sets = summary.stats.character_sets.keys();
+    Ok(exit_code(
+        cli,
+        character_sets.any(|character_set| !character_set.is_gsm()),
+        summary.stats.exceeds_max_parts > 0,
+    ))
+}
+
+fn run(cli: &Cli) -> Result<ExitCode, Box<dyn Error>> {
+    if let Some(path) = &cli.batch {
+        return run_batch(cli, path);
+    }
+    let message = cli.message()?;
+    let options = cli.options.splitter_options();
+    let splitter = SplitSms::new(options);
+    splitter.validate()?;
+    let non_gsm = splitter.non_gsm_characters(&message);
+    let result = splitter.split(message);
+    let mut out = io::stdout().lock();
+    if cli.json {
+        writeln!(out, "{}", result.to_json()?)?;
+    } else {
+        write_table(&mut out, &result, &non_gsm)?;
+    }
+    Ok(exit_code(
+        cli,
+        !result.character_set.is_gsm(),
+        result.exceeds_max_parts,
+    ))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    run(&cli).unwrap_or_else(|e| {
+        eprintln!("sms-splitter: {}", e);
+        ExitCode::FAILURE
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from([&["sms-splitter"], args].concat()).unwrap()
+    }
+
+    #[test]
+    fn flags_to_options() {
+        Cli::command().debug_assert();
+        let cli = parse(&[
+            "--shift-tables",
+            "--max-parts",
+            "3",
+            "--max-parts-policy",
+            "truncate",
+            "--split-mode",
+            "independent",
+            "--numbering",
+            "{part}/{total} ",
+            "--numbering-position",
+            "suffix",
+            "--encoding",
+            "gsm",
+            "hello",
+        ]);
+        let options = cli.options.splitter_options();
+        assert!(options.support_shift_tables);
+        assert_eq!(options.max_parts, Some(3));
+        assert_eq!(options.max_parts_policy, MaxPartsPolicy::Truncate);
+        assert_eq!(options.split_mode, SplitMode::Independent);
+        assert_eq!(
+            options.part_numbering,
+            Some(PartNumbering::new(
+                String::from("{part}/{total} "),
+                NumberingPosition::Suffix
+            ))
+        );
+        assert_eq!(options.encoding, Encoding::Gsm(Replacement::Lookalike));
+        assert_eq!(cli.message().unwrap(), "hello");
+
+        let options = parse(&["--replacement", "?"]).options.splitter_options();
+        assert_eq!(options.encoding, Encoding::Gsm(Replacement::Char('?')));
+        assert!(Cli::try_parse_from(["sms-splitter", "--split-mode", "words"]).is_err());
+        assert!(Cli::try_parse_from(["sms-splitter", "--file", "a.txt", "hello"]).is_err());
+        assert!(Cli::try_parse_from(["sms-splitter", "--column", "text"]).is_err());
+        let cli = parse(&["--batch", "variants.txt", "--format", "jsonl"]);
+        assert_eq!(cli.format, Some(Format::Jsonl));
+    }
+
+    #[test]
+    fn table() {
+        let message = "it\u{2019}s \u{1F600}";
+        let result = SplitSms::default().split(String::from(message));
+        let mut out = Vec::new();
+        let non_gsm = SplitSms::default().non_gsm_characters(message);
+        write_table(&mut out, &result, &non_gsm).unwrap();
+        let table = String::from_utf8(out).unwrap();
+        assert!(table.starts_with("encoding   ucs2\nparts      1\n"));
+        assert!(table.contains("non-GSM    \u{2019} U+2019, \u{1F600} U+1F600\n"));
+        assert!(table.ends_with("1     6       14     it\u{2019}s \u{1F600}\n"));
+    }
+
+    #[test]
+    fn require_gsm() {
+        let message = String::from("it\u{2019}s");
+        for (args, code) in [
+            (&["--require-gsm"][..], ExitCode::from(EXIT_ENCODING)),
+            (&["--require-gsm", "--encoding", "gsm"], ExitCode::SUCCESS),
+            (&[], ExitCode::SUCCESS),
+        ] {
+            let cli = parse(args);
+            let result = SplitSms::new(cli.options.splitter_options()).split(message.clone());
+            assert_eq!(exit_code(&cli, !result.character_set.is_gsm(), false), code);
+        }
+    }
+}
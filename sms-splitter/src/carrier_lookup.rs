@@ -0,0 +1,252 @@
+use std::{
+    collections::HashMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{carrier_profile::CarrierProfile, splitter_options::SplitterOptions, SplitSms};
+
+const BUNDLED: &str = include_str!("../data/carriers.json");
+
+/// Maps destinations to carrier profiles, by E.164 number prefix or by MCC/MNC:
+///
+/// ```json
+/// {
+///   "default": "gsm",
+///   "profiles": [],
+///   "prefixes": { "1555": "cdma" },
+///   "networks": { "310-004": "cdma", "234": "gsm" }
+/// }
+/// ```
+///
+/// Prefixes are country codes or number ranges without the `+`, the longest match wins. Network
+/// keys are `mcc-mnc`, or a bare `mcc` for the whole country. Profiles are referenced by name,
+/// either a built-in one or one of `profiles`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CarrierLookup {
+    /// Profile for destinations no rule matches, [`CarrierProfile::gsm`] when unset.
+    #[serde(default)]
+    pub default: Option<String>,
+    #[serde(default)]
+    pub profiles: Vec<CarrierProfile>,
+    #[serde(default)]
+    pub prefixes: HashMap<String, String>,
+    #[serde(default)]
+    pub networks: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupError {
+    Parse(String),
+    /// A rule names a profile that is neither built in nor defined in the data.
+    UnknownProfile(String),
+}
+
+impl std::fmt::Display for LookupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LookupError::Parse(e) => write!(f, "invalid carrier data: {}", e),
+            LookupError::UnknownProfile(name) => write!(f, "unknown carrier profile {}", name),
+        }
+    }
+}
+
+impl std::error::Error for LookupError {}
+
+impl CarrierLookup {
+    /// The data shipped with the crate. It only has network rules: ported numbers keep their
+    /// prefix, so a prefix does not tell the carrier and none are bundled.
+    pub fn bundled() -> CarrierLookup {
+        CarrierLookup::from_json(BUNDLED).expect("bundled carrier data is valid")
+    }
+
+    pub fn from_json(data: &str) -> Result<CarrierLookup, LookupError> {
+        let lookup: CarrierLookup =
+            serde_json::from_str(data).map_err(|e| LookupError::Parse(e.to_string()))?;
+        lookup.validated()
+    }
+
+    /// Applies `overrides` on top of this data: its profiles replace ones of the same name and
+    /// its rules replace rules for the same prefix or network.
+    pub fn with_overrides(
+        mut self,
+        overrides: CarrierLookup,
+    ) -> Result<CarrierLookup, LookupError> {
+        for profile in overrides.profiles {
+            self.profiles.retain(|p| p.name != profile.name);
+            self.profiles.push(profile);
+        }
+        self.prefixes.extend(overrides.prefixes);
+        self.networks.extend(overrides.networks);
+        if overrides.default.is_some() {
+            self.default = overrides.default;
+        }
+        self.validated()
+    }
+
+    fn validated(self) -> Result<CarrierLookup, LookupError> {
+        let names = self
+            .default
+            .iter()
+            .chain(self.prefixes.values())
+            .chain(self.networks.values());
+        for name in names {
+            if self.profile(name).is_none() {
+                return Err(LookupError::UnknownProfile(name.clone()));
+            }
+        }
+        Ok(self)
+    }
+
+    /// A profile defined in the data, or else a built-in one.
+    pub fn profile(&self, name: &str) -> Option<CarrierProfile> {
+        self.profiles
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()
+            .or_else(|| CarrierProfile::by_name(name))
+    }
+
+    pub fn default_profile(&self) -> CarrierProfile {
+        self.default
+            .as_deref()
+            .and_then(|name| self.profile(name))
+            .unwrap_or_default()
+    }
+
+    /// Profile for an E.164 number. Spaces, dashes and a leading `+` or `00` are ignored. The
+    /// bundled data has no prefix rules, so every number gets the default profile until some are
+    /// added with [`CarrierLookup::with_overrides`]; look up by [`CarrierLookup::for_network`]
+    /// when the network is known.
+    pub fn for_number(&self, number: &str) -> CarrierProfile {
+        let number = number.trim();
+        let number = number
+            .strip_prefix('+')
+            .or_else(|| number.strip_prefix("00"))
+            .unwrap_or(number);
+        let digits: String = number.chars().filter(|c| c.is_ascii_digit()).collect();
+        (1..=digits.len())
+            .rev()
+            .find_map(|len| self.prefixes.get(&digits[..len]))
+            .and_then(|name| self.profile(name))
+            .unwrap_or_else(|| self.default_profile())
+    }
+
+    /// Profile for a mobile network, falling back to a rule for the country code alone.
+    pub fn for_network(&self, mcc: &str, mnc: &str) -> CarrierProfile {
+        self.networks
+            .get(&format!("{}-{}", mcc, mnc))
+            .or_else(|| self.networks.get(mcc))
+            .and_then(|name| self.profile(name))
+            .unwrap_or_else(|| self.default_profile())
+    }
+}
+
+impl SplitSms {
+    /// A splitter using the counting rules of the recipient's carrier, by
+    /// [`CarrierLookup::for_number`]. The bundled data has no prefix rules, use
+    /// [`SplitSms::for_recipient_network`] when the recipient's network is known.
+    pub fn for_recipient(
+        options: SplitterOptions,
+        lookup: &CarrierLookup,
+        number: &str,
+    ) -> SplitSms {
+        SplitSms::with_profile(options, lookup.for_number(number))
+    }
+
+    /// A splitter using the counting rules of the recipient's network, by
+    /// [`CarrierLookup::for_network`], e.g. with the MCC/MNC an HLR lookup returned.
+    pub fn for_recipient_network(
+        options: SplitterOptions,
+        lookup: &CarrierLookup,
+        mcc: &str,
+        mnc: &str,
+    ) -> SplitSms {
+        SplitSms::with_profile(options, lookup.for_network(mcc, mnc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_networks() {
+        let lookup = CarrierLookup::bundled();
+        assert_eq!(lookup.for_network("311", "480").name, "cdma");
+        assert_eq!(lookup.for_network("234", "15").name, "gsm");
+        assert_eq!(lookup.for_network("999", "01").name, "gsm");
+    }
+
+    #[test]
+    fn recipient_network() {
+        let lookup = CarrierLookup::bundled();
+        let message = "a".repeat(200);
+        let result =
+            SplitSms::for_recipient_network(SplitterOptions::default(), &lookup, "311", "480")
+                .split(message.clone());
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.parts[0].bytes, 160);
+        let result =
+            SplitSms::for_recipient_network(SplitterOptions::default(), &lookup, "234", "15")
+                .split(message);
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.parts[0].bytes, 153);
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let lookup = CarrierLookup::bundled()
+            .with_overrides(
+                CarrierLookup::from_json(r#"{ "prefixes": { "1": "gsm-16bit", "1555": "cdma" } }"#)
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(lookup.for_number("+1 555 0100").name, "cdma");
+        assert_eq!(lookup.for_number("0012125550100").name, "gsm-16bit");
+        assert_eq!(lookup.for_number("+33612345678").name, "gsm");
+        // bundled rules survive the override
+        assert_eq!(lookup.for_network("310", "004").name, "cdma");
+    }
+
+    #[test]
+    fn custom_profiles() {
+        let lookup = CarrierLookup::from_json(
+            r#"{
+                "default": "short",
+                "profiles": [{
+                    "name": "short",
+                    "shift_tables": false,
+                    "extension_septets": 2,
+                    "gsm": { "single": 100, "multi": 100 },
+                    "unicode": { "single": 70, "multi": 70 },
+                    "udh": "none",
+                    "max_parts": 3
+                }],
+                "networks": { "505": "gsm" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(lookup.for_network("505", "01").name, "gsm");
+        assert_eq!(lookup.for_network("999", "01").name, "short");
+        let result = SplitSms::for_recipient(SplitterOptions::default(), &lookup, "+61400000000")
+            .split("a".repeat(150));
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.parts[0].bytes, 100);
+    }
+
+    #[test]
+    fn unknown_profile() {
+        assert_eq!(
+            CarrierLookup::from_json(r#"{ "prefixes": { "1": "tdma" } }"#).unwrap_err(),
+            LookupError::UnknownProfile("tdma".to_string())
+        );
+        assert!(matches!(
+            CarrierLookup::from_json(r#"{ "networks": 1 }"#),
+            Err(LookupError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,215 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{splitter_error::SplitError, splitter_options::MAX_CONCATENATED_PARTS};
+
+/// Part capacities in septets for GSM and octets for Unicode, like `SplitterPart::bytes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Capacity {
+    pub single: usize,
+    pub multi: usize,
+}
+
+/// The user data header a carrier expects on concatenated parts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum UdhStyle {
+    /// No concatenation, every part is delivered as an independent message.
+    None,
+    /// 6 octet header with an 8-bit reference number.
+    #[default]
+    Concatenated8Bit,
+    /// 7 octet header with a 16-bit reference number.
+    Concatenated16Bit,
+}
+
+impl UdhStyle {
+    pub fn octets(&self) -> usize {
+        match self {
+            UdhStyle::None => 0,
+            UdhStyle::Concatenated8Bit => 6,
+            UdhStyle::Concatenated16Bit => 7,
+        }
+    }
+}
+
+/// The counting rules of a carrier: alphabet, capacities, UDH style and part limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CarrierProfile {
+    pub name: String,
+    /// Whether the carrier accepts national language shift tables. `support_shift_tables` only
+    /// takes effect when this is set.
+    pub shift_tables: bool,
+    /// Septets counted for an extension table character: 2 for the escape and the character,
+    /// 1 on networks that count characters.
+    pub extension_septets: usize,
+    pub gsm: Capacity,
+    pub unicode: Capacity,
+    pub udh: UdhStyle,
+    pub max_parts: usize,
+}
+
+impl Default for CarrierProfile {
+    fn default() -> Self {
+        CarrierProfile::gsm()
+    }
+}
+
+impl CarrierProfile {
+    // capacities left by a concatenation header of the given style
+    fn with_udh(name: &str, udh: UdhStyle) -> CarrierProfile {
+        let octets = 140 - udh.octets();
+        CarrierProfile {
+            name: name.to_string(),
+            shift_tables: true,
+            extension_septets: 2,
+            gsm: Capacity {
+                single: 160,
+                multi: octets * 8 / 7,
+            },
+            unicode: Capacity {
+                single: 140,
+                multi: octets / 2 * 2,
+            },
+            udh,
+            max_parts: MAX_CONCATENATED_PARTS,
+        }
+    }
+
+    /// GSM 03.38 with 8-bit reference concatenation: 160/153 septets, 140/134 octets.
+    pub fn gsm() -> CarrierProfile {
+        CarrierProfile::with_udh("gsm", UdhStyle::Concatenated8Bit)
+    }
+
+    /// GSM 03.38 with 16-bit reference concatenation: 160/152 septets, 140/132 octets.
+    pub fn gsm_16bit_reference() -> CarrierProfile {
+        CarrierProfile::with_udh("gsm-16bit", UdhStyle::Concatenated16Bit)
+    }
+
+    /// CDMA heritage networks: 160/70 characters without concatenation or shift tables, and
+    /// extension characters counted once.
+    pub fn cdma() -> CarrierProfile {
+        CarrierProfile {
+            shift_tables: false,
+            extension_septets: 1,
+            ..CarrierProfile::with_udh("cdma", UdhStyle::None)
+        }
+    }
+
+    pub fn builtin() -> Vec<CarrierProfile> {
+        vec![
+            CarrierProfile::gsm(),
+            CarrierProfile::gsm_16bit_reference(),
+            CarrierProfile::cdma(),
+        ]
+    }
+
+    pub fn by_name(name: &str) -> Option<CarrierProfile> {
+        CarrierProfile::builtin()
+            .into_iter()
+            .find(|p| p.name == name)
+    }
+
+    /// Rejects profiles that cannot hold a character in a part.
+    pub fn validate(&self) -> Result<(), SplitError> {
+        if self.extension_septets == 0 || self.max_parts == 0 {
+            return Err(SplitError::InvalidOptions(format!(
+                "carrier profile {} has no extension septets or parts",
+                self.name
+            )));
+        }
+        // a part holds at least an extension character or a surrogate pair
+        let too_small = self.gsm.multi < self.extension_septets || self.unicode.multi < 4;
+        if too_small || self.gsm.single < self.gsm.multi || self.unicode.single < self.unicode.multi
+        {
+            return Err(SplitError::InvalidOptions(format!(
+                "carrier profile {} has unusable capacities",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Capacities for the encoding. Without concatenation every part gets the single capacity.
+    pub fn capacity(&self, is_gsm: bool) -> Capacity {
+        let capacity = if is_gsm { self.gsm } else { self.unicode };
+        if self.udh == UdhStyle::None {
+            return Capacity {
+                single: capacity.single,
+                multi: capacity.single,
+            };
+        }
+        capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_capacities() {
+        let gsm = CarrierProfile::gsm();
+        assert_eq!(
+            gsm.capacity(true),
+            Capacity {
+                single: 160,
+                multi: 153
+            }
+        );
+        assert_eq!(
+            gsm.capacity(false),
+            Capacity {
+                single: 140,
+                multi: 134
+            }
+        );
+        let gsm_16bit = CarrierProfile::gsm_16bit_reference();
+        assert_eq!(gsm_16bit.capacity(true).multi, 152);
+        assert_eq!(gsm_16bit.capacity(false).multi, 132);
+        let cdma = CarrierProfile::by_name("cdma").unwrap();
+        assert_eq!(
+            cdma.capacity(true),
+            Capacity {
+                single: 160,
+                multi: 160
+            }
+        );
+        assert_eq!(
+            cdma.capacity(false),
+            Capacity {
+                single: 140,
+                multi: 140
+            }
+        );
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn profile_from_json() {
+        let profile: CarrierProfile = serde_json::from_str(
+            r#"{
+                "name": "custom",
+                "shift_tables": false,
+                "extension_septets": 2,
+                "gsm": { "single": 160, "multi": 153 },
+                "unicode": { "single": 140, "multi": 134 },
+                "udh": "concatenated8_bit",
+                "max_parts": 6
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(profile.max_parts, 6);
+        assert_eq!(profile.udh, UdhStyle::Concatenated8Bit);
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use alloc::vec::Vec;
+
+use crate::gsm_validator::{self, GsmValidator};
+
+/// The encoding a message is sent in. GSM 7-bit and UCS-2 serialize as `"GSM"` and `"Unicode"`,
+/// the names results had before this type, and the other plain values in snake case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CharacterSet {
+    /// GSM 03.38 default alphabet and extension table, in septets.
+    #[cfg_attr(feature = "serde", serde(rename = "GSM", alias = "gsm7"))]
+    Gsm7,
+    /// GSM 7-bit with characters only found in the national language shift tables, and the
+    /// tables they need.
+    Gsm7National(NationalTables),
+    /// UCS-2, UTF-16 in practice, in octets.
+    #[cfg_attr(feature = "serde", serde(rename = "Unicode", alias = "ucs2"))]
+    Ucs2,
+    /// ISO 8859-1, for routes that accept it. Not chosen by the splitters.
+    Latin1,
+    /// 8-bit data. Not chosen by the splitters.
+    Binary,
+}
+
+impl CharacterSet {
+    pub fn is_gsm(&self) -> bool {
+        matches!(self, CharacterSet::Gsm7 | CharacterSet::Gsm7National(_))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CharacterSet::Gsm7 => "gsm7",
+            CharacterSet::Gsm7National(_) => "gsm7_national",
+            CharacterSet::Ucs2 => "ucs2",
+            CharacterSet::Latin1 => "latin1",
+            CharacterSet::Binary => "binary",
+        }
+    }
+}
+
+impl core::fmt::Display for CharacterSet {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A language with national language shift tables, see 3GPP TS 23.038 6.2.1.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum NationalLanguage {
+    Turkish,
+    Spanish,
+    Portuguese,
+}
+
+/// The shift tables a [`CharacterSet::Gsm7National`] message needs. Both are `None` when no
+/// single language covers its characters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NationalTables {
+    /// Replaces the default alphabet.
+    pub locking: Option<NationalLanguage>,
+    /// Replaces the extension table.
+    pub single: Option<NationalLanguage>,
+}
+
+impl NationalTables {
+    /// The tables of the first language covering the national characters of `message`, the
+    /// single shift table alone when it is enough.
+    pub fn for_message(message: &str) -> NationalTables {
+        NationalTables::for_chars(message.chars())
+    }
+
+    pub(crate) fn for_chars(chars: impl Iterator<Item = char>) -> NationalTables {
+        let national: Vec<u16> = chars
+            .filter(|c| is_national(*c))
+            .map(|c| c as u16)
+            .collect();
+        let languages = [
+            NationalLanguage::Turkish,
+            NationalLanguage::Spanish,
+            NationalLanguage::Portuguese,
+        ];
+        for language in languages {
+            let (locking, single) = gsm_validator::shift_tables(language);
+            if national.iter().all(|c| single.contains(c)) {
+                return NationalTables {
+                    locking: None,
+                    single: Some(language),
+                };
+            }
+            if national.iter().all(|c| locking.contains(c)) {
+                return NationalTables {
+                    locking: Some(language),
+                    single: None,
+                };
+            }
+            if national
+                .iter()
+                .all(|c| locking.contains(c) || single.contains(c))
+            {
+                return NationalTables {
+                    locking: Some(language),
+                    single: Some(language),
+                };
+            }
+        }
+        NationalTables::default()
+    }
+}
+
+// only found in the national language shift tables, not in the default alphabet
+pub(crate) fn is_national(c: char) -> bool {
+    let validator = GsmValidator::new();
+    c.len_utf16() == 1
+        && !validator.validate_character(c as u16)
+        && validator.validate_character_with_shift_table(c as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn national_tables() {
+        let tables = |message| NationalTables::for_message(message);
+        assert_eq!(
+            tables("Ş"),
+            NationalTables {
+                locking: None,
+                single: Some(NationalLanguage::Turkish)
+            }
+        );
+        assert_eq!(
+            tables("ç"),
+            NationalTables {
+                locking: Some(NationalLanguage::Turkish),
+                single: None
+            }
+        );
+        assert_eq!(tables("á").single, Some(NationalLanguage::Spanish));
+        assert_eq!(tables("ª").locking, Some(NationalLanguage::Portuguese));
+        // Turkish and Portuguese only
+        assert_eq!(tables("ıª"), NationalTables::default());
+    }
+}
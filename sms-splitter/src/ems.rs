@@ -0,0 +1,399 @@
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::{
+    carrier_profile::UdhStyle,
+    encoded_char::encode_chars,
+    pdu::{self, InformationElement, PduError, PduOptions, SubmitPdu},
+    splitter_error::SplitError,
+    splitter_options::{MaxPartsPolicy, SplitMode},
+    splitter_result::{SplitterPart, SplitterResult},
+    SplitSms, SplitSmsResult, MAX_CONCATENATED_PARTS,
+};
+
+// TS 23.040 9.2.3.24.10.1.1
+const TEXT_FORMATTING: u8 = 0x0A;
+const USER_DATA_OCTETS: usize = 140;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    #[default]
+    Language,
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FontSize {
+    #[default]
+    Normal,
+    Large,
+    Small,
+}
+
+/// An EMS text formatting element over `length` characters starting at `start`.
+///
+/// Ranges passed to [`SplitSms::split_with_formats`] are `char` indices into the message. Ranges
+/// returned per part are relative to the part and counted in characters of the part encoding:
+/// septet characters for GSM, UCS-2 code units for Unicode.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TextFormat {
+    pub start: usize,
+    pub length: usize,
+    pub alignment: Alignment,
+    pub font_size: FontSize,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    /// Foreground colour in the low nibble, background colour in the high nibble.
+    pub color: Option<u8>,
+}
+
+impl TextFormat {
+    pub fn new(start: usize, length: usize) -> TextFormat {
+        TextFormat {
+            start,
+            length,
+            ..Default::default()
+        }
+    }
+
+    pub fn end(&self) -> usize {
+        self.start + self.length
+    }
+
+    /// The formatting mode octet.
+    pub fn mode(&self) -> u8 {
+        (self.alignment as u8)
+            | (self.font_size as u8) << 2
+            | (self.bold as u8) << 4
+            | (self.italic as u8) << 5
+            | (self.underline as u8) << 6
+            | (self.strikethrough as u8) << 7
+    }
+
+    pub fn to_information_element(&self) -> InformationElement {
+        let mut data = vec![self.start as u8, self.length as u8, self.mode()];
+        if let Some(color) = self.color {
+            data.push(color);
+        }
+        InformationElement::new(TEXT_FORMATTING, data)
+    }
+
+    // octets taken in the user data header
+    fn octets(&self) -> usize {
+        if self.color.is_some() {
+            return 6;
+        }
+        5
+    }
+
+    fn intersects(&self, start: usize, end: usize) -> bool {
+        self.start < end && self.end() > start
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FormattedSplit {
+    pub result: SplitSmsResult,
+    /// Formatting of every part, with ranges relative to that part.
+    pub formats: Vec<Vec<TextFormat>>,
+}
+
+impl FormattedSplit {
+    pub fn information_elements(&self) -> Vec<Vec<InformationElement>> {
+        self.formats
+            .iter()
+            .map(|formats| formats.iter().map(|f| f.to_information_element()).collect())
+            .collect()
+    }
+
+    pub fn submit_pdus(&self, options: &PduOptions) -> Result<Vec<SubmitPdu>, PduError> {
+        pdu::submit_pdus_with_elements(&self.result, &self.information_elements(), options)
+    }
+}
+
+impl SplitSms {
+    /// Splits a message carrying EMS text formatting. Each part loses the octets of the
+    /// formatting elements that fall into it, and formats crossing a part boundary are split.
+    ///
+    /// `max_parts` and its policy apply like in [`SplitSms::try_split`]. The formatting elements
+    /// travel in the user data header, so profiles without one are rejected, and so are part
+    /// numbering and [`SplitMode::Independent`], which move part boundaries the formats rely on.
+    pub fn split_with_formats(
+        &self,
+        message: String,
+        formats: &[TextFormat],
+    ) -> Result<FormattedSplit, SplitError> {
+        self.validate()?;
+        if self.profile.udh == UdhStyle::None {
+            return Err(SplitError::InvalidOptions(
+                "EMS formatting needs a user data header, the carrier profile has none".into(),
+            ));
+        }
+        if self.options.part_numbering.is_some() {
+            return Err(SplitError::InvalidOptions(
+                "part numbering does not apply to EMS formatting".into(),
+            ));
+        }
+        if self.options.split_mode == SplitMode::Independent {
+            return Err(SplitError::InvalidOptions(
+                "independent split mode does not apply to EMS formatting".into(),
+            ));
+        }
+        let is_gsm = self.is_gsm(&message);
+        let chars = encode_chars(&message, is_gsm, &self.splitter_options(), &self.profile);
+        let formats: Vec<&TextFormat> = formats
+            .iter()
+            .filter(|f| f.length > 0 && f.start < chars.len())
+            .collect();
+        // the concatenation element, without the header length octet
+        let concatenation_octets = self.profile.udh.octets() - 1;
+        let single = self.profile.capacity(is_gsm).single;
+        let capacity = |header: usize| {
+            let octets = USER_DATA_OCTETS.saturating_sub(header);
+            if is_gsm {
+                return (octets * 8 / 7).min(single);
+            }
+            (octets / 2 * 2).min(single)
+        };
+        let header = |concatenated: bool, start: usize, end: usize| {
+            let mut octets: usize = formats
+                .iter()
+                .filter(|f| f.intersects(start, end))
+                .map(|f| f.octets())
+                .sum();
+            if concatenated {
+                octets += concatenation_octets;
+            }
+            if octets > 0 {
+                octets += 1;
+            }
+            octets
+        };
+
+        let total_bytes: usize = chars.iter().map(|c| c.bytes).sum();
+        let mut ranges = Vec::new();
+        if total_bytes <= capacity(header(false, 0, chars.len())) {
+            ranges.push((0, chars.len(), total_bytes));
+        } else {
+            let mut start = 0;
+            while start < chars.len() {
+                let mut end = start;
+                let mut bytes = 0;
+                while end < chars.len() {
+                    let max = capacity(header(true, start, end + 1));
+                    if bytes + chars[end].bytes > max && end > start {
+                        break;
+                    }
+                    bytes += chars[end].bytes;
+                    end += 1;
+                }
+                ranges.push((start, end, bytes));
+                start = end;
+            }
+        }
+        let exceeds_max_parts = ranges.len() > self.max_parts();
+        let policy = self.options.max_parts_policy;
+        if exceeds_max_parts && policy == MaxPartsPolicy::Truncate {
+            ranges.truncate(self.max_parts());
+        } else if ranges.len() > MAX_CONCATENATED_PARTS {
+            return Err(SplitError::TooManyParts {
+                parts: ranges.len(),
+                max_parts: MAX_CONCATENATED_PARTS,
+            });
+        } else if exceeds_max_parts && policy == MaxPartsPolicy::Error {
+            return Err(SplitError::TooManyParts {
+                parts: ranges.len(),
+                max_parts: self.max_parts(),
+            });
+        }
+        let total_bytes: usize = ranges.iter().map(|(_, _, bytes)| bytes).sum();
+        let total_length = ranges.last().map_or(0, |(_, end, _)| *end);
+
+        let mut parts = Vec::new();
+        let mut part_formats = Vec::new();
+        for (start, end, bytes) in &ranges {
+            let mut content = String::new();
+            if !self.options.summary {
+                content = chars[*start..*end].iter().map(|c| c.content).collect();
+            }
+            parts.push(SplitterPart::new(content, end - start, *bytes));
+            let units =
+                |from: usize, to: usize| -> usize { chars[from..to].iter().map(|c| c.units).sum() };
+            part_formats.push(
+                formats
+                    .iter()
+                    .filter(|f| f.intersects(*start, *end))
+                    .map(|f| {
+                        let from = f.start.max(*start);
+                        let to = f.end().min(*end);
+                        TextFormat {
+                            start: units(*start, from),
+                            length: units(from, to),
+                            ..(*f).clone()
+                        }
+                    })
+                    .collect(),
+            );
+        }
+
+        let mut split = SplitterResult {
+            parts,
+            total_length,
+            total_bytes,
+        };
+        split.set_offsets(&message, ranges.iter().map(|(start, end, _)| end - start));
+
+        let (last_start, last_end, last_bytes) = ranges[ranges.len() - 1];
+        let last_capacity = capacity(header(ranges.len() > 1, last_start, last_end));
+        let char_bytes = if is_gsm { 1 } else { 2 };
+        let mut result = SplitSmsResult::new(
+            self.character_set(&message, is_gsm),
+            split.parts,
+            total_bytes,
+            total_length,
+            last_capacity.saturating_sub(last_bytes) / char_bytes,
+        );
+        result.exceeds_max_parts = exceeds_max_parts;
+        Ok(FormattedSplit {
+            result,
+            formats: part_formats,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        carrier_profile::CarrierProfile, character_set::CharacterSet,
+        splitter_options::SplitterOptions,
+    };
+    use alloc::{format, string::ToString};
+
+    fn bold(start: usize, length: usize) -> TextFormat {
+        TextFormat {
+            bold: true,
+            ..TextFormat::new(start, length)
+        }
+    }
+
+    #[test]
+    fn matches_split_without_formats() {
+        let splitter = SplitSms::default();
+        for message in [
+            "a".repeat(160),
+            "a".repeat(161),
+            "€".repeat(100),
+            "📡".repeat(40),
+            String::new(),
+        ] {
+            let expected = splitter.split(message.clone());
+            let formatted = splitter.split_with_formats(message, &[]).unwrap();
+            assert_eq!(formatted.result, expected);
+        }
+    }
+
+    #[test]
+    fn single_part_reserves_header() {
+        let formatted = SplitSms::default()
+            .split_with_formats("Hello World!".to_string(), &[bold(6, 5)])
+            .unwrap();
+        assert_eq!(formatted.result.parts.len(), 1);
+        assert_eq!(formatted.formats, vec![vec![bold(6, 5)]]);
+        // UDHL and a 5 octet element leave 134 octets, 153 septets
+        assert_eq!(formatted.result.remaining_in_part, 153 - 12);
+        assert_eq!(formatted.formats[0][0].mode(), 0x10);
+    }
+
+    #[test]
+    fn format_split_across_parts() {
+        let formatted = SplitSms::default()
+            .split_with_formats("a".repeat(300), &[bold(140, 30)])
+            .unwrap();
+        let lengths: Vec<usize> = formatted.result.parts.iter().map(|p| p.length).collect();
+        // 11 octet header leaves 147 septets in the formatted parts
+        assert_eq!(lengths, vec![147, 147, 6]);
+        assert_eq!(
+            formatted.formats,
+            vec![vec![bold(140, 7)], vec![bold(0, 23)], vec![]]
+        );
+        assert_eq!(formatted.result.remaining_in_part, 153 - 6);
+    }
+
+    #[test]
+    fn unicode_positions_in_code_units() {
+        let message = format!("📡📡{}", "ก".repeat(70));
+        let formatted = SplitSms::default()
+            .split_with_formats(message, &[bold(1, 2)])
+            .unwrap();
+        assert_eq!(formatted.result.character_set, CharacterSet::Ucs2);
+        assert_eq!(formatted.formats[0], vec![bold(2, 3)]);
+    }
+
+    #[test]
+    fn pdus_carry_formatting_elements() {
+        let formatted = SplitSms::default()
+            .split_with_formats("Hello World!".to_string(), &[bold(6, 5)])
+            .unwrap();
+        let pdus = formatted
+            .submit_pdus(&PduOptions::new("+31641600986".to_string(), 0))
+            .unwrap();
+        // UDHI set, UDHL 5, text formatting element
+        let hex = pdus[0].to_hex();
+        assert!(hex.starts_with("0051"));
+        assert!(hex.contains("050A03060510"));
+    }
+
+    #[test]
+    fn profile_and_options() {
+        // a 16-bit reference takes an octet more from every part
+        let formatted = SplitSms::with_profile(
+            SplitterOptions::default(),
+            CarrierProfile::gsm_16bit_reference(),
+        )
+        .split_with_formats("a".repeat(300), &[bold(140, 30)])
+        .unwrap();
+        let lengths: Vec<usize> = formatted.result.parts.iter().map(|p| p.length).collect();
+        assert_eq!(lengths, vec![146, 146, 8]);
+
+        let options = SplitterOptions {
+            max_parts: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            SplitSms::new(options.clone())
+                .split_with_formats("a".repeat(400), &[])
+                .unwrap_err(),
+            SplitError::TooManyParts {
+                parts: 3,
+                max_parts: 2
+            }
+        );
+        let formatted = SplitSms::new(SplitterOptions {
+            max_parts_policy: MaxPartsPolicy::Truncate,
+            ..options
+        })
+        .split_with_formats("a".repeat(400), &[bold(350, 10)])
+        .unwrap();
+        assert!(formatted.result.exceeds_max_parts);
+        assert_eq!(formatted.result.parts.len(), 2);
+        assert_eq!(formatted.result.length, 306);
+        assert_eq!(formatted.formats, vec![vec![], vec![]]);
+
+        for splitter in [
+            SplitSms::with_profile(SplitterOptions::default(), CarrierProfile::cdma()),
+            SplitSms::new(SplitterOptions {
+                split_mode: SplitMode::Independent,
+                ..Default::default()
+            }),
+        ] {
+            assert!(matches!(
+                splitter.split_with_formats("Hello".to_string(), &[]),
+                Err(SplitError::InvalidOptions(_))
+            ));
+        }
+    }
+}
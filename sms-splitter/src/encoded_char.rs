@@ -0,0 +1,79 @@
+use alloc::vec::Vec;
+
+use crate::{
+    carrier_profile::CarrierProfile, gsm_splitter::GsmSplitter, splitter_options::SplitterOptions,
+};
+
+// A character of the message as it will be sent: GSM characters outside the alphabet are
+// replaced, like GsmSplitter does.
+pub(crate) struct EncodedChar {
+    pub content: char,
+    // septets for GSM, octets for Unicode
+    pub bytes: usize,
+    // characters as numbered in the part: septet characters or UCS-2 code units
+    pub units: usize,
+    // byte offset of the character in the message
+    pub offset: usize,
+}
+
+pub(crate) fn encode_chars(
+    message: &str,
+    is_gsm: bool,
+    options: &SplitterOptions,
+    profile: &CarrierProfile,
+) -> Vec<EncodedChar> {
+    let gsm = GsmSplitter::with_profile(options.clone(), profile.clone());
+    message
+        .char_indices()
+        .map(|(offset, c)| {
+            if !is_gsm {
+                return EncodedChar {
+                    content: c,
+                    bytes: c.len_utf16() * 2,
+                    units: c.len_utf16(),
+                    offset,
+                };
+            }
+            match gsm.septets(c) {
+                Some(bytes) => EncodedChar {
+                    content: c,
+                    bytes,
+                    units: 1,
+                    offset,
+                },
+                None => EncodedChar {
+                    content: gsm.replacement(c),
+                    bytes: gsm.sent_septets(c),
+                    units: 1,
+                    offset,
+                },
+            }
+        })
+        .collect()
+}
+
+// end of the longest run starting at `start` that fits in `capacity`, at least one character
+pub(crate) fn fill(chars: &[EncodedChar], start: usize, capacity: usize) -> usize {
+    let mut end = start;
+    let mut bytes = 0;
+    while end < chars.len() && (end == start || bytes + chars[end].bytes <= capacity) {
+        bytes += chars[end].bytes;
+        end += 1;
+    }
+    end
+}
+
+// moves `end` back to just after the last whitespace, or else punctuation, of the run
+pub(crate) fn word_break(chars: &[EncodedChar], start: usize, end: usize) -> usize {
+    if end >= chars.len() {
+        return end;
+    }
+    let after = |is_break: fn(&char) -> bool| {
+        (start + 1..=end)
+            .rev()
+            .find(|i| is_break(&chars[i - 1].content))
+    };
+    after(|c| c.is_whitespace())
+        .or_else(|| after(|c| c.is_ascii_punctuation()))
+        .unwrap_or(end)
+}
@@ -1,18 +1,32 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
 use crate::{
+    carrier_profile::CarrierProfile,
     gsm_validator::GsmValidator,
-    splitter_options::SplitterOptions,
+    splitter_error::SplitError,
+    splitter_options::{Encoding, Replacement, SplitterOptions},
     splitter_result::{SplitterPart, SplitterResult},
+    suggestions::lookalike,
 };
 
 // Debug
 #[derive(Debug,Default)]
 pub struct GsmSplitter {
     options: SplitterOptions,
+    profile: CarrierProfile,
 }
 
 impl GsmSplitter {
     pub fn new(options: SplitterOptions) -> GsmSplitter {
-        GsmSplitter { options }
+        GsmSplitter::with_profile(options, CarrierProfile::default())
+    }
+
+    /// Splits with the capacities and extension table counting of a carrier.
+    pub fn with_profile(options: SplitterOptions, profile: CarrierProfile) -> GsmSplitter {
+        GsmSplitter { options, profile }
     }
 
     fn is_high_surrogate(&self, code: u16) -> bool {
@@ -21,16 +35,38 @@ impl GsmSplitter {
 
     // SplitterResult
     pub fn split(&self, message: String) -> SplitterResult {
-        let message = message.encode_utf16().collect::<Vec<u16>>();
+        self.split_lossy(&message).0
+    }
+
+    /// Like [`GsmSplitter::split`], but fails instead of replacing a character it cannot encode.
+    pub fn try_split(&self, message: String) -> Result<SplitterResult, SplitError> {
+        match self.split_lossy(&message) {
+            (result, None) => Ok(result),
+            (_, Some(error)) => Err(error),
+        }
+    }
+
+    // splits replacing undecodable characters, with the first such error
+    pub(crate) fn split_lossy(&self, message: &str) -> (SplitterResult, Option<SplitError>) {
+        let (mut result, error) = self.split_codes(message.encode_utf16().collect());
+        let lengths: Vec<usize> = result.parts.iter().map(|p| p.length).collect();
+        result.set_offsets(message, lengths);
+        (result, error)
+    }
+
+    fn split_codes(&self, message: Vec<u16>) -> (SplitterResult, Option<SplitError>) {
         if message.is_empty() {
-            return SplitterResult::empty();
+            return (SplitterResult::empty(), None);
         }
+        let mut error = None;
         let mut messages: Vec<SplitterPart> = Vec::new();
         let mut length = 0;
         let mut bytes = 0;
         let mut total_bytes = 0;
         let mut total_length = 0;
         let mut message_part = String::new();
+        let capacity = self.profile.capacity(true);
+        let extension_septets = self.profile.extension_septets;
         let bank = |bytes: &mut usize,
                     length: &mut usize,
                     total_bytes: &mut usize,
@@ -52,21 +88,20 @@ impl GsmSplitter {
         // println!("count: {}", count);
         let mut i = 0;
         while i < count {
-            let space = *'\u{0020}'
-                .to_string()
-                .encode_utf16()
-                .collect::<Vec<u16>>().first()
-                .unwrap();
-            let mut code = message.get(i).unwrap_or(&space);
+            let space = 0x20;
+            let mut code = *message.get(i).unwrap_or(&space);
             // println!("bytes: {} {}", bytes, code);
-            if !self.validate_character(*code) {
-                if self.is_high_surrogate(*code) {
+            if !self.validate_character(code) {
+                if self.is_high_surrogate(code) {
                     i += 1;
                 }
-                code = &space;
-            } else if self.validate_extended_character(*code) {
+                let character =
+                    core::char::from_u32(code as u32).unwrap_or(char::REPLACEMENT_CHARACTER);
+                code = self.replacement(character) as u16;
+            }
+            if self.validate_extended_character(code) {
                 // println!("extended character bytes {}", bytes);
-                if bytes == 152 {
+                if bytes > 0 && bytes + extension_septets > capacity.multi {
                     bank(
                         &mut bytes,
                         &mut length,
@@ -76,14 +111,18 @@ impl GsmSplitter {
                         &mut messages,
                     );
                 }
-                bytes += 1;
+                bytes += extension_septets.saturating_sub(1);
             }
             bytes += 1;
             length += 1;
             if !self.options.summary {
-                message_part.push(std::char::from_u32(*code as u32).unwrap());
+                let character = core::char::from_u32(code as u32).unwrap_or_else(|| {
+                    error.get_or_insert(SplitError::InvalidCharacter(code as u32));
+                    ' '
+                });
+                message_part.push(character);
             }
-            if bytes == 153 {
+            if bytes >= capacity.multi {
                 bank(
                     &mut bytes,
                     &mut length,
@@ -106,7 +145,7 @@ impl GsmSplitter {
                 &mut messages,
             );
         }
-        if messages.get(1).is_some() && total_bytes <= 160 {
+        if messages.get(1).is_some() && total_bytes <= capacity.single {
             let mut parts = Vec::new();
             let content: String = String::from("");
             // options.summary ? undefined : messages[0].content + messages[1].content
@@ -119,17 +158,19 @@ impl GsmSplitter {
                     total_bytes,
                 ));
             }
-            return SplitterResult {
+            let result = SplitterResult {
                 parts: parts.clone(),
                 total_length,
                 total_bytes,
             };
+            return (result, error);
         }
-        SplitterResult {
+        let result = SplitterResult {
             parts: messages,
             total_length,
             total_bytes,
-        }
+        };
+        (result, error)
     }
 
     fn validate_character(&self, character_code: u16) -> bool {
@@ -146,13 +187,47 @@ impl GsmSplitter {
         }
         GsmValidator::new().validate_extended_character(character_code)
     }
+
+    // septets taken by a character, None when it is replaced by a space
+    pub(crate) fn septets(&self, character: char) -> Option<usize> {
+        if character.len_utf16() > 1 || !self.validate_character(character as u16) {
+            return None;
+        }
+        if self.validate_extended_character(character as u16) {
+            return Some(self.profile.extension_septets);
+        }
+        Some(1)
+    }
+
+    // what a character outside the alphabet is sent as, a space unless GSM is forced
+    pub(crate) fn replacement(&self, character: char) -> char {
+        let replacement = match self.options.encoding {
+            Encoding::Gsm(Replacement::Lookalike) => lookalike(character)
+                .and_then(|text| text.chars().next())
+                .unwrap_or('?'),
+            Encoding::Gsm(Replacement::Char(replacement)) => replacement,
+            Encoding::Auto | Encoding::Ucs2 => ' ',
+        };
+        if self.septets(replacement).is_none() {
+            return ' ';
+        }
+        replacement
+    }
+
+    // septets of a character, or of its replacement
+    pub(crate) fn sent_septets(&self, character: char) -> usize {
+        self.septets(character)
+            .or_else(|| self.septets(self.replacement(character)))
+            .unwrap_or(1)
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "serde_json"))]
 mod tests {
     use super::*;
     // import json test data from  test folder
     use std::fs::File;
+    use std::println;
     use std::io::prelude::*;
     use std::path::Path;
 
@@ -1,107 +1,137 @@
-pub struct GsmValidator {
-    gsm_char_codes: Vec<u16>,
-    gsme_char_codes: Vec<u16>,
-    gsme_tr_char_codes: Vec<u16>,
-    gsm_tr_char_codes: Vec<u16>,
-    gsm_es_char_codes: Vec<u16>,
-    gsme_es_char_codes: Vec<u16>,
-    gsm_pt_char_codes: Vec<u16>,
-    gsme_pt_char_codes: Vec<u16>,
+use alloc::{string::String, vec::Vec};
+
+use crate::character_set::NationalLanguage;
+
+// '@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ\x20!"#¤%&\'()*+,-./0123456789:;<=>?¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüà\f^{}\\[~]|€'
+const GSM_CHAR_CODES: [u16; 137] = [
+    10, 12, 13, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52,
+    53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76,
+    77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 97, 98, 99, 100,
+    101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119,
+    120, 121, 122, 123, 124, 125, 126, 161, 163, 164, 165, 167, 191, 196, 197, 198, 199, 201, 209,
+    214, 216, 220, 223, 224, 228, 229, 230, 232, 233, 236, 241, 242, 246, 248, 249, 252, 915, 916,
+    920, 923, 926, 928, 931, 934, 936, 937, 8364,
+];
+// '\f|^€{}[~]\\'
+const GSME_CHAR_CODES: [u16; 10] = [12, 91, 92, 93, 94, 123, 124, 125, 126, 8364];
+// '\f^{}\[~]|'
+const GSME_TR_CHAR_CODES: [u16; 16] = [
+    12, 91, 92, 93, 94, 123, 124, 125, 126, 286, 287, 304, 305, 350, 351, 8364,
+];
+// '@£$¥€éùıòÇ\nĞğ\rÅåΔ_ΦΓΛΩΠΨΣΘΞŞşßÉ\x20!"#¤%&\'()*+,-./0123456789:;<=>?İABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§çabcdefghijklmnopqrstuvwxyzäöñüà\f^{}\[~]|'
+const GSM_TR_CHAR_CODES: [u16; 136] = [
+    10, 12, 13, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52,
+    53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76,
+    77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 97, 98, 99, 100,
+    101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119,
+    120, 121, 122, 123, 124, 125, 126, 163, 164, 165, 167, 196, 197, 199, 201, 209, 214, 220, 223,
+    224, 228, 229, 231, 233, 241, 242, 246, 249, 252, 286, 287, 304, 305, 350, 351, 915, 916, 920,
+    923, 926, 928, 931, 934, 936, 937, 8364,
+];
+// '@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ\x20!"#¤%&\'()*+,-./0123456789:;<=>?¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüàç\f^{}\\[~]|ÁÍÓÚá€íóú'
+const GSM_ES_CHAR_CODES: [u16; 146] = [
+    10, 12, 13, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52,
+    53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76,
+    77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 97, 98, 99, 100,
+    101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119,
+    120, 121, 122, 123, 124, 125, 126, 161, 163, 164, 165, 167, 191, 193, 196, 197, 198, 199, 201,
+    205, 209, 211, 214, 216, 218, 220, 223, 224, 225, 228, 229, 230, 231, 232, 233, 236, 237, 241,
+    242, 243, 246, 248, 249, 250, 252, 915, 916, 920, 923, 926, 928, 931, 934, 936, 937, 8364,
+];
+// 'ç\f^{}\\[~]|ÁÍÓÚá€íóú'
+const GSME_ES_CHAR_CODES: [u16; 19] = [
+    12, 91, 92, 93, 94, 123, 124, 125, 126, 193, 205, 211, 218, 225, 231, 237, 243, 250, 8364,
+];
+// '@£$¥êéúíóç\nÔô\rÁáΔ_ªÇÀ∞^\\€Ó|ÂâÊÉ\x20!"#º%&\'()*+,-./0123456789:;<=>?ÍABCDEFGHIJKLMNOPQRSTUVWXYZÃÕÚÜ§~abcdefghijklmnopqrstuvwxyzãõ`üà\fΦΓ^ΩΠΨΣΘ{}\\[~]|'
+const GSM_PT_CHAR_CODES: [u16; 140] = [
+    10, 12, 13, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52,
+    53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76,
+    77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99,
+    100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118,
+    119, 120, 121, 122, 123, 124, 125, 126, 163, 165, 167, 170, 186, 192, 193, 194, 195, 199, 201,
+    202, 205, 211, 212, 213, 218, 220, 224, 225, 226, 227, 231, 233, 234, 237, 242, 243, 244, 245,
+    250, 252, 915, 916, 920, 928, 931, 934, 936, 937, 8364, 8734,
+];
+// '\fΦΓ^ΩΠΨΣΘ{}\\[~]|'
+const GSME_PT_CHAR_CODES: [u16; 36] = [
+    12, 91, 92, 93, 94, 123, 124, 125, 126, 193, 194, 195, 202, 205, 211, 212, 213, 218, 225, 226,
+    227, 231, 234, 237, 242, 243, 245, 250, 915, 920, 928, 931, 934, 936, 937, 8364,
+];
+
+// bitsets over the character codes, large enough for the highest one, ∞ (8734)
+const WORDS: usize = 8734 / 64 + 1;
+
+struct CharSet([u64; WORDS]);
+
+impl CharSet {
+    const fn new(tables: &[&[u16]]) -> CharSet {
+        let mut bits = [0; WORDS];
+        let mut t = 0;
+        while t < tables.len() {
+            let mut i = 0;
+            while i < tables[t].len() {
+                let code = tables[t][i] as usize;
+                bits[code / 64] |= 1 << (code % 64);
+                i += 1;
+            }
+            t += 1;
+        }
+        CharSet(bits)
+    }
+
+    fn contains(&self, code: u16) -> bool {
+        let code = code as usize;
+        code / 64 < WORDS && self.0[code / 64] & (1 << (code % 64)) != 0
+    }
 }
 
-impl Default for GsmValidator {
-    fn default() -> Self {
-        Self::new()
+static GSM: CharSet = CharSet::new(&[&GSM_CHAR_CODES]);
+// shift table characters, without the default alphabet
+static GSM_SHIFT: CharSet =
+    CharSet::new(&[&GSM_TR_CHAR_CODES, &GSM_ES_CHAR_CODES, &GSM_PT_CHAR_CODES]);
+static GSM_ALL: CharSet = CharSet::new(&[
+    &GSM_CHAR_CODES,
+    &GSM_TR_CHAR_CODES,
+    &GSM_ES_CHAR_CODES,
+    &GSM_PT_CHAR_CODES,
+]);
+static GSME: CharSet = CharSet::new(&[&GSME_CHAR_CODES]);
+static GSME_ALL: CharSet = CharSet::new(&[
+    &GSME_CHAR_CODES,
+    &GSME_TR_CHAR_CODES,
+    &GSME_ES_CHAR_CODES,
+    &GSME_PT_CHAR_CODES,
+]);
+
+// the locking and single shift table characters of a language
+pub(crate) fn shift_tables(language: NationalLanguage) -> (&'static [u16], &'static [u16]) {
+    match language {
+        NationalLanguage::Turkish => (&GSM_TR_CHAR_CODES, &GSME_TR_CHAR_CODES),
+        NationalLanguage::Spanish => (&GSM_ES_CHAR_CODES, &GSME_ES_CHAR_CODES),
+        NationalLanguage::Portuguese => (&GSM_PT_CHAR_CODES, &GSME_PT_CHAR_CODES),
     }
 }
 
+/// Checks characters against the GSM 03.38 alphabet and the Turkish, Spanish and Portuguese
+/// shift tables. The tables are static bitsets, so a validator is free to create and copy.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GsmValidator {}
+
 impl GsmValidator {
     pub fn new() -> GsmValidator {
-        GsmValidator {
-            // '@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ\x20!"#¤%&\'()*+,-./0123456789:;<=>?¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüà\f^{}\\[~]|€'
-            gsm_char_codes: vec![
-                10, 12, 13, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
-                50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70,
-                71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91,
-                92, 93, 94, 95, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110,
-                111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124, 125, 126,
-                161, 163, 164, 165, 167, 191, 196, 197, 198, 199, 201, 209, 214, 216, 220, 223,
-                224, 228, 229, 230, 232, 233, 236, 241, 242, 246, 248, 249, 252, 915, 916, 920,
-                923, 926, 928, 931, 934, 936, 937, 8364,
-            ],
-            // '\f|^€{}[~]\\'
-            gsme_char_codes: vec![12, 91, 92, 93, 94, 123, 124, 125, 126, 8364],
-            // '\f^{}\[~]|'
-            gsme_tr_char_codes: vec![
-                12, 91, 92, 93, 94, 123, 124, 125, 126, 286, 287, 304, 305, 350, 351, 8364,
-            ],
-            // '@£$¥€éùıòÇ\nĞğ\rÅåΔ_ΦΓΛΩΠΨΣΘΞŞşßÉ\x20!"#¤%&\'()*+,-./0123456789:;<=>?İABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§çabcdefghijklmnopqrstuvwxyzäöñüà\f^{}\[~]|'
-            gsm_tr_char_codes: vec![
-                10, 12, 13, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
-                50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70,
-                71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91,
-                92, 93, 94, 95, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110,
-                111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124, 125, 126,
-                163, 164, 165, 167, 196, 197, 199, 201, 209, 214, 220, 223, 224, 228, 229, 231,
-                233, 241, 242, 246, 249, 252, 286, 287, 304, 305, 350, 351, 915, 916, 920, 923,
-                926, 928, 931, 934, 936, 937, 8364,
-            ],
-            // '@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ\x20!"#¤%&\'()*+,-./0123456789:;<=>?¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüàç\f^{}\\[~]|ÁÍÓÚá€íóú'
-            gsm_es_char_codes: vec![
-                10, 12, 13, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
-                50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70,
-                71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91,
-                92, 93, 94, 95, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110,
-                111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124, 125, 126,
-                161, 163, 164, 165, 167, 191, 193, 196, 197, 198, 199, 201, 205, 209, 211, 214,
-                216, 218, 220, 223, 224, 225, 228, 229, 230, 231, 232, 233, 236, 237, 241, 242,
-                243, 246, 248, 249, 250, 252, 915, 916, 920, 923, 926, 928, 931, 934, 936, 937,
-                8364,
-            ],
-            // 'ç\f^{}\\[~]|ÁÍÓÚá€íóú'
-            gsme_es_char_codes: vec![
-                12, 91, 92, 93, 94, 123, 124, 125, 126, 193, 205, 211, 218, 225, 231, 237, 243,
-                250, 8364,
-            ],
-            // '@£$¥êéúíóç\nÔô\rÁáΔ_ªÇÀ∞^\\€Ó|ÂâÊÉ\x20!"#º%&\'()*+,-./0123456789:;<=>?ÍABCDEFGHIJKLMNOPQRSTUVWXYZÃÕÚÜ§~abcdefghijklmnopqrstuvwxyzãõ`üà\fΦΓ^ΩΠΨΣΘ{}\\[~]|'
-            gsm_pt_char_codes: vec![
-                10, 12, 13, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
-                50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70,
-                71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91,
-                92, 93, 94, 95, 96, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109,
-                110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124, 125,
-                126, 163, 165, 167, 170, 186, 192, 193, 194, 195, 199, 201, 202, 205, 211, 212,
-                213, 218, 220, 224, 225, 226, 227, 231, 233, 234, 237, 242, 243, 244, 245, 250,
-                252, 915, 916, 920, 928, 931, 934, 936, 937, 8364, 8734,
-            ],
-            // '\fΦΓ^ΩΠΨΣΘ{}\\[~]|'
-            gsme_pt_char_codes: vec![
-                12, 91, 92, 93, 94, 123, 124, 125, 126, 193, 194, 195, 202, 205, 211, 212, 213,
-                218, 225, 226, 227, 231, 234, 237, 242, 243, 245, 250, 915, 920, 928, 931, 934,
-                936, 937, 8364,
-            ],
-        }
+        GsmValidator {}
     }
 
     pub fn exists_in_array(&self, code: u16, array: Vec<u16>) -> bool {
-        for e in array {
-            if code == e {
-                return true;
-            }
-        }
-        false
+        array.contains(&code)
     }
 
     pub fn validate_character(self, char_code: u16) -> bool {
-        self.exists_in_array(char_code, self.gsm_char_codes.clone())
+        GSM.contains(char_code)
     }
 
     pub fn validate_character_with_shift_table(self, char_code: u16) -> bool {
-        // concat GSM_TR_charCodes, GSM_ES_charCodes, GSM_PT_charCodes
-        let mut shift_table = self.gsm_tr_char_codes.clone();
-        shift_table.append(&mut self.gsm_es_char_codes.clone());
-        shift_table.append(&mut self.gsm_pt_char_codes.clone());
-        self.exists_in_array(char_code, shift_table)
+        GSM_SHIFT.contains(char_code)
     }
 
     pub fn validate_message_in_char_codes_list(
@@ -109,66 +139,45 @@ impl GsmValidator {
         message: String,
         char_codes: Vec<u16>,
     ) -> bool {
-        for c in message.encode_utf16().collect::<Vec<u16>>(){
-            if !self.exists_in_array(c, char_codes.clone()) {
-                return false;
-            }
-        }
-        true
+        message.encode_utf16().all(|c| char_codes.contains(&c))
     }
 
     pub fn validate_message(self, message: String) -> bool {
-        self.validate_message_in_char_codes_list(message, self.gsm_char_codes.clone())
+        self.validate_str(&message)
+    }
+
+    /// [`GsmValidator::validate_message`] without taking ownership of the message.
+    pub fn validate_str(self, message: &str) -> bool {
+        message.encode_utf16().all(|c| GSM.contains(c))
     }
 
     pub fn validate_message_with_shift_table(self, message: String) -> bool {
-        // var charCodes = [GSM_charCodes, GSM_TR_charCodes, GSM_ES_charCodes, GSM_PT_charCodes];
-        let mut char_codes = self.gsm_char_codes.clone();
-        char_codes.append(&mut self.gsm_tr_char_codes.clone());
-        char_codes.append(&mut self.gsm_es_char_codes.clone());
-        char_codes.append(&mut self.gsm_pt_char_codes.clone());
-        for c in message.chars() {
-            // validate_message_in_char_codes_list
-            if self.validate_message_in_char_codes_list(c.to_string(), char_codes.clone()) {
-                return true;
-            }
-        }
-        false
+        self.validate_str_with_shift_table(&message)
+    }
+
+    // true when any character, with all of its code units, is in one of the tables
+    pub fn validate_str_with_shift_table(self, message: &str) -> bool {
+        message
+            .chars()
+            .any(|c| c.encode_utf16(&mut [0; 2]).iter().all(|u| GSM_ALL.contains(*u)))
     }
 
     pub fn validate_extended_character(self, char_code: u16) -> bool {
-        self.exists_in_array(char_code, self.gsme_char_codes.clone())
+        GSME.contains(char_code)
     }
+
     // validateExtendedCharacterWithShiftTable
     pub fn validate_extended_character_with_shift_table(self, char_code: u16) -> bool {
-        // var charCodes = GSMe_charCodes.concat(GSMe_TR_charCodes, GSMe_ES_charCodes, GSMe_PT_charCodes);
-        let mut char_codes = self.gsme_char_codes.clone();
-        char_codes.append(&mut self.gsme_tr_char_codes.clone());
-        char_codes.append(&mut self.gsme_es_char_codes.clone());
-        char_codes.append(&mut self.gsme_pt_char_codes.clone());
-        self.exists_in_array(char_code, char_codes)
-    }
-}
-
-impl Clone for GsmValidator {
-    fn clone(&self) -> Self {
-        GsmValidator {
-            gsm_char_codes: self.gsm_char_codes.clone(),
-            gsm_tr_char_codes: self.gsm_tr_char_codes.clone(),
-            gsm_es_char_codes: self.gsm_es_char_codes.clone(),
-            gsm_pt_char_codes: self.gsm_pt_char_codes.clone(),
-            gsme_char_codes: self.gsme_char_codes.clone(),
-            gsme_tr_char_codes: self.gsme_tr_char_codes.clone(),
-            gsme_es_char_codes: self.gsme_es_char_codes.clone(),
-            gsme_pt_char_codes: self.gsme_pt_char_codes.clone(),
-        }
+        GSME_ALL.contains(char_code)
     }
 }
 
 // test
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::clone_on_copy)]
 mod tests {
     use super::*;
+    use alloc::string::ToString;
 
     // Validating a message of every valid GSM characters
     #[test]
@@ -288,4 +297,43 @@ mod tests {
             .unwrap();
         assert_eq!(gsm_validator.validate_character(message), false);
     }
+
+    // borrowed messages validate like owned ones
+    #[test]
+    fn validate_str() {
+        let gsm_validator = GsmValidator::new();
+        for message in ["Hello World!", "∞Ø", "Ağaç", "\u{1F433}", ""] {
+            assert_eq!(
+                gsm_validator.validate_str(message),
+                gsm_validator.validate_message(message.to_string())
+            );
+            assert_eq!(
+                gsm_validator.validate_str_with_shift_table(message),
+                gsm_validator.validate_message_with_shift_table(message.to_string())
+            );
+        }
+    }
+
+    // the bitsets hold exactly the listed codes
+    #[test]
+    fn bitsets_match_tables() {
+        let shift = [&GSM_TR_CHAR_CODES[..], &GSM_ES_CHAR_CODES, &GSM_PT_CHAR_CODES].concat();
+        let extended = [
+            &GSME_CHAR_CODES[..],
+            &GSME_TR_CHAR_CODES,
+            &GSME_ES_CHAR_CODES,
+            &GSME_PT_CHAR_CODES,
+        ]
+        .concat();
+        for code in 0..=u16::MAX {
+            assert_eq!(GSM.contains(code), GSM_CHAR_CODES.contains(&code));
+            assert_eq!(GSM_SHIFT.contains(code), shift.contains(&code));
+            assert_eq!(
+                GSM_ALL.contains(code),
+                GSM_CHAR_CODES.contains(&code) || shift.contains(&code)
+            );
+            assert_eq!(GSME.contains(code), GSME_CHAR_CODES.contains(&code));
+            assert_eq!(GSME_ALL.contains(code), extended.contains(&code));
+        }
+    }
 }
@@ -0,0 +1,473 @@
+use alloc::{string::String, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    carrier_profile::CarrierProfile,
+    character_set::{is_national, CharacterSet, NationalTables},
+    gsm_splitter::GsmSplitter,
+    splitter_options::{Encoding, MaxPartsPolicy, SplitMode, SplitterOptions},
+    SplitSms, SplitSmsResult,
+};
+
+// a character with its cost in both encodings, so switching encoding needs no revalidation
+#[derive(Debug, Clone, Copy)]
+struct TypedChar {
+    content: char,
+    // counts towards a GSM message, see SplitSms::validate_message
+    valid: bool,
+    // only in the national language shift tables
+    national: bool,
+    septets: usize,
+    units: usize,
+}
+
+/// Keeps the encoding, part count and `remaining_in_part` of a message up to date as it is
+/// edited. Edits only validate the characters they add or remove, and update the counts in
+/// constant time while no character straddles a part boundary.
+///
+/// Once a two-septet character or a surrogate pair does, boundaries are recounted from the part
+/// an edit falls in, part by part, until one lands on a boundary of the text before the edit.
+/// The parts from there on are kept with their cached sizes. An edit that shifts every later
+/// boundary, like one character typed early in a long message whose parts are all full, is
+/// still recounted to the end of the text, in time linear in what follows the edit. Either way
+/// the characters and cached parts after an edit are moved along with it, a copy of the rest of
+/// the text, and typing at the end costs the same however long the message is.
+///
+/// Positions are `char` indices. Independent split mode and part numbering move boundaries in
+/// ways that cannot be tracked by edit, and split the whole message on every edit, in time
+/// linear in its length.
+#[derive(Debug)]
+pub struct IncrementalSplitter {
+    splitter: SplitSms,
+    gsm: GsmSplitter,
+    chars: Vec<TypedChar>,
+    // characters counting towards GSM, and those only in the shift tables
+    valid: usize,
+    national: usize,
+    septets: usize,
+    units: usize,
+    // characters taking two septets, or a surrogate pair
+    wide_septets: usize,
+    wide_units: usize,
+    // starts and sizes of the parts packed at the multipart capacity, for `starts_gsm`, from
+    // the first part up to `resume`, where counting picks up after an edit
+    starts: Vec<usize>,
+    sums: Vec<usize>,
+    resume: usize,
+    // parts of the text past the last edit, as (start, size), to reuse once the parts before
+    // them line up again
+    stale: Vec<(usize, usize)>,
+    starts_gsm: bool,
+    parts: usize,
+    remaining_in_part: usize,
+    // before truncating to `max_parts`
+    exceeds_max_parts: bool,
+    // of the last full split, for the modes that need one
+    split_character_set: Option<CharacterSet>,
+}
+
+impl IncrementalSplitter {
+    pub fn new(options: SplitterOptions) -> IncrementalSplitter {
+        IncrementalSplitter::with_profile(options, CarrierProfile::default())
+    }
+
+    pub fn with_profile(options: SplitterOptions, profile: CarrierProfile) -> IncrementalSplitter {
+        let splitter = SplitSms::with_profile(options, profile);
+        let gsm = GsmSplitter::with_profile(splitter.splitter_options(), splitter.profile.clone());
+        let mut incremental = IncrementalSplitter {
+            splitter,
+            gsm,
+            chars: Vec::new(),
+            valid: 0,
+            national: 0,
+            septets: 0,
+            units: 0,
+            wide_septets: 0,
+            wide_units: 0,
+            starts: Vec::new(),
+            sums: Vec::new(),
+            resume: 0,
+            stale: Vec::new(),
+            starts_gsm: true,
+            parts: 0,
+            remaining_in_part: 0,
+            exceeds_max_parts: false,
+            split_character_set: None,
+        };
+        incremental.refresh();
+        incremental
+    }
+
+    fn typed(&self, content: char) -> TypedChar {
+        TypedChar {
+            content,
+            valid: self
+                .splitter
+                .validate_message(content.encode_utf8(&mut [0; 4])),
+            national: is_national(content),
+            septets: self.gsm.sent_septets(content),
+            units: content.len_utf16(),
+        }
+    }
+
+    fn count(&mut self, c: &TypedChar, added: bool) {
+        let wide_septets = usize::from(c.septets > 1);
+        let wide_units = usize::from(c.units > 1);
+        if added {
+            self.valid += usize::from(c.valid);
+            self.national += usize::from(c.national);
+            self.septets += c.septets;
+            self.units += c.units;
+            self.wide_septets += wide_septets;
+            self.wide_units += wide_units;
+        } else {
+            self.valid -= usize::from(c.valid);
+            self.national -= usize::from(c.national);
+            self.septets -= c.septets;
+            self.units -= c.units;
+            self.wide_septets -= wide_septets;
+            self.wide_units -= wide_units;
+        }
+    }
+
+    /// Inserts `text` before the character at `position`, or at the end past it.
+    pub fn insert(&mut self, position: usize, text: &str) {
+        let position = position.min(self.chars.len());
+        let typed: Vec<TypedChar> = text.chars().map(|c| self.typed(c)).collect();
+        for c in typed.iter() {
+            self.count(c, true);
+        }
+        let added = typed.len();
+        self.chars.splice(position..position, typed);
+        self.edited(position, position, added);
+    }
+
+    /// Deletes the characters in `range`, clamped to the message.
+    pub fn delete(&mut self, range: Range<usize>) {
+        let end = range.end.min(self.chars.len());
+        let start = range.start.min(end);
+        let removed: Vec<TypedChar> = self.chars.drain(start..end).collect();
+        for c in removed.iter() {
+            self.count(c, false);
+        }
+        self.edited(start, end, 0);
+    }
+
+    // `start..end` of the text was replaced by `added` characters. Parts ending before the edit
+    // still hold, as a part only depends on its text and the character after it. Parts after
+    // the edit are shifted along and kept to reuse.
+    fn edited(&mut self, start: usize, end: usize, added: usize) {
+        let mut edited = Vec::new();
+        while self.resume >= start {
+            let (Some(s), Some(sum)) = (self.starts.pop(), self.sums.pop()) else {
+                break;
+            };
+            edited.push((s, sum));
+            self.resume = s;
+        }
+        let after = edited.into_iter().rev().chain(self.stale.drain(..));
+        self.stale = after
+            .filter(|(s, _)| *s > end)
+            .map(|(s, sum)| (s - (end - start) + added, sum))
+            .collect();
+        self.refresh();
+    }
+
+    /// Whether the message goes out in GSM, counting the pattern of the part numbering.
+    pub fn is_gsm(&self) -> bool {
+        match self.splitter.options.encoding {
+            Encoding::Auto => {}
+            Encoding::Gsm(_) => return true,
+            Encoding::Ucs2 => return false,
+        }
+        let numbering = self.splitter.options.part_numbering.as_ref();
+        if !numbering.is_none_or(|n| self.splitter.is_gsm(&n.pattern)) {
+            return false;
+        }
+        if self.splitter.options.support_shift_tables && self.splitter.profile.shift_tables {
+            return self.valid > 0;
+        }
+        self.valid == self.chars.len()
+    }
+
+    pub fn character_set(&self) -> CharacterSet {
+        let is_gsm = self.is_gsm();
+        // from the full split that counting the numbering needs
+        if let Some(character_set) = self.split_character_set {
+            return character_set;
+        }
+        if !is_gsm {
+            return CharacterSet::Ucs2;
+        }
+        if self.splitter.splitter_options().support_shift_tables && self.national > 0 {
+            let national = self.chars.iter().filter(|c| c.national);
+            return CharacterSet::Gsm7National(NationalTables::for_chars(
+                national.map(|c| c.content),
+            ));
+        }
+        CharacterSet::Gsm7
+    }
+
+    pub fn parts(&self) -> usize {
+        self.parts
+    }
+
+    pub fn remaining_in_part(&self) -> usize {
+        self.remaining_in_part
+    }
+
+    pub fn exceeds_max_parts(&self) -> bool {
+        self.exceeds_max_parts
+    }
+
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().map(|c| c.content).collect()
+    }
+
+    /// The full split of the current text.
+    pub fn split(&self) -> SplitSmsResult {
+        self.splitter.split(self.text())
+    }
+
+    fn refresh(&mut self) {
+        let options = &self.splitter.options;
+        if options.split_mode == SplitMode::Independent || options.part_numbering.is_some() {
+            let result = self.split();
+            self.parts = result.parts.len();
+            self.remaining_in_part = result.remaining_in_part;
+            self.exceeds_max_parts = result.exceeds_max_parts;
+            if options.part_numbering.is_some() {
+                self.split_character_set = Some(result.character_set);
+            }
+            return;
+        }
+        let is_gsm = self.is_gsm();
+        let capacity = self.splitter.profile.capacity(is_gsm);
+        let (total, wide, char_bytes) = if is_gsm {
+            (self.septets, self.wide_septets, 1)
+        } else {
+            (self.units * 2, self.wide_units, 2)
+        };
+        if total <= capacity.single {
+            self.parts = 1;
+            self.remaining_in_part = (capacity.single - total) / char_bytes;
+            self.exceeds_max_parts = false;
+            return;
+        }
+        // without wide characters every part but the last is full
+        if wide == 0 && capacity.multi > 0 && capacity.multi.is_multiple_of(char_bytes) {
+            let parts = total.div_ceil(capacity.multi);
+            let last = total - (parts - 1) * capacity.multi;
+            self.counted(parts, last, capacity.multi);
+            return;
+        }
+
+        if self.starts_gsm != is_gsm {
+            self.starts.clear();
+            self.sums.clear();
+            self.resume = 0;
+            self.stale.clear();
+            self.starts_gsm = is_gsm;
+        }
+        let cost = |c: &TypedChar| if is_gsm { c.septets } else { c.units * 2 };
+        let mut start = self.resume;
+        loop {
+            let mut end = start;
+            let mut bytes = 0;
+            while end < self.chars.len()
+                && (end == start || bytes + cost(&self.chars[end]) <= capacity.multi)
+            {
+                bytes += cost(&self.chars[end]);
+                end += 1;
+            }
+            self.starts.push(start);
+            self.sums.push(bytes);
+            self.resume = end;
+            if end >= self.chars.len() {
+                break;
+            }
+            // the parts from a boundary the edit did not move are the same as before it
+            if let Ok(i) = self.stale.binary_search_by_key(&end, |(s, _)| *s) {
+                let (starts, sums): (Vec<usize>, Vec<usize>) = self.stale.drain(i..).unzip();
+                self.starts.extend(starts);
+                self.sums.extend(sums);
+                self.resume = self.chars.len();
+                break;
+            }
+            start = end;
+        }
+        self.stale.clear();
+        let parts = self.starts.len();
+        let last = self.sums[parts - 1];
+        let kept = self.sums[parts.min(self.splitter.max_parts()) - 1];
+        self.counted(parts, last, kept);
+    }
+
+    // sets the counts of a message packed at the multipart capacity into `parts` parts, the last
+    // taking `last` bytes, truncated under MaxPartsPolicy::Truncate to `max_parts` parts, the last
+    // kept taking `kept` bytes
+    fn counted(&mut self, parts: usize, last: usize, kept: usize) {
+        let is_gsm = self.is_gsm();
+        let capacity = self.splitter.profile.capacity(is_gsm);
+        let char_bytes = if is_gsm { 1 } else { 2 };
+        let max_parts = self.splitter.max_parts();
+        self.exceeds_max_parts = parts > max_parts;
+        let (parts, last) = match self.splitter.options.max_parts_policy {
+            MaxPartsPolicy::Truncate if self.exceeds_max_parts => (max_parts, kept),
+            _ => (parts, last),
+        };
+        // as SplitSms::calculate_remaining
+        let max = if parts == 1 {
+            capacity.single
+        } else {
+            capacity.multi
+        };
+        self.parts = parts;
+        self.remaining_in_part = max.saturating_sub(last) / char_bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::part_numbering::{NumberingPosition, PartNumbering};
+
+    fn assert_counts(incremental: &IncrementalSplitter) {
+        let result = incremental.split();
+        assert_eq!(incremental.character_set(), result.character_set);
+        assert_eq!(incremental.parts(), result.parts.len());
+        assert_eq!(incremental.remaining_in_part(), result.remaining_in_part);
+        assert_eq!(incremental.exceeds_max_parts(), result.exceeds_max_parts);
+    }
+
+    #[test]
+    fn typing_switches_encoding() {
+        let mut incremental = IncrementalSplitter::new(SplitterOptions::default());
+        assert_counts(&incremental);
+        incremental.insert(0, &"a".repeat(150));
+        assert_eq!(incremental.remaining_in_part(), 10);
+        incremental.insert(75, "ก");
+        assert_eq!(incremental.character_set(), CharacterSet::Ucs2);
+        assert_eq!(incremental.parts(), 3);
+        assert_counts(&incremental);
+        incremental.delete(75..76);
+        assert_eq!(incremental.character_set(), CharacterSet::Gsm7);
+        assert_eq!(incremental.parts(), 1);
+        assert_counts(&incremental);
+    }
+
+    fn edit_and_compare(options: SplitterOptions) {
+        let pieces = ["a", "{", "€", "hello ", "ก", "😀", "[x]", "\n"];
+        let mut incremental = IncrementalSplitter::new(options);
+        let mut seed: usize = 7;
+        let mut next = |bound: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % bound.max(1)
+        };
+        for round in 0..600 {
+            let len = incremental.len();
+            if round % 5 == 4 && len > 0 {
+                let start = next(len);
+                let end = start + next(20);
+                incremental.delete(start..end);
+            } else {
+                // mostly GSM text, so the message keeps switching encoding
+                let piece = pieces[next(pieces.len() * 3).min(pieces.len() - 1)];
+                let position = if next(2) == 0 { len } else { next(len + 1) };
+                incremental.insert(position, &piece.repeat(1 + next(10)));
+            }
+            assert_counts(&incremental);
+        }
+    }
+
+    #[test]
+    fn matches_full_split_under_edits() {
+        edit_and_compare(SplitterOptions::default());
+        for pattern in ["({part}/{total}) ", "«{part}» "] {
+            edit_and_compare(SplitterOptions {
+                part_numbering: Some(PartNumbering::new(
+                    String::from(pattern),
+                    NumberingPosition::Prefix,
+                )),
+                ..Default::default()
+            });
+        }
+        for max_parts in [1, 2, 3] {
+            edit_and_compare(SplitterOptions {
+                max_parts: Some(max_parts),
+                max_parts_policy: MaxPartsPolicy::Truncate,
+                ..Default::default()
+            });
+        }
+    }
+
+    #[test]
+    fn early_edits_keep_later_parts() {
+        // 76 two-septet characters fill a part but for one septet
+        let mut incremental = IncrementalSplitter::new(SplitterOptions::default());
+        incremental.insert(0, &"{".repeat(760));
+        assert_eq!(incremental.parts(), 10);
+        let starts = incremental.starts.clone();
+        incremental.insert(0, "a");
+        assert_counts(&incremental);
+        // the first part takes the character, the later ones move along with the text
+        assert_eq!(incremental.starts[0], 0);
+        assert_eq!(
+            incremental.starts[1..],
+            starts[1..].iter().map(|s| s + 1).collect::<Vec<_>>()[..]
+        );
+        incremental.delete(0..1);
+        assert_counts(&incremental);
+        assert_eq!(incremental.starts, starts);
+    }
+
+    #[test]
+    fn truncate() {
+        let mut incremental = IncrementalSplitter::new(SplitterOptions {
+            max_parts: Some(2),
+            max_parts_policy: MaxPartsPolicy::Truncate,
+            ..Default::default()
+        });
+        incremental.insert(0, &"a".repeat(1000));
+        assert_eq!(incremental.parts(), 2);
+        assert_eq!(incremental.remaining_in_part(), 0);
+        assert!(incremental.exceeds_max_parts());
+        assert_counts(&incremental);
+    }
+
+    #[test]
+    fn numbering_encoding() {
+        let mut incremental = IncrementalSplitter::new(SplitterOptions {
+            part_numbering: Some(PartNumbering::new(
+                String::from("«{part}» "),
+                NumberingPosition::Prefix,
+            )),
+            ..Default::default()
+        });
+        incremental.insert(0, "Hello");
+        assert!(!incremental.is_gsm());
+        assert_eq!(incremental.character_set(), CharacterSet::Ucs2);
+        assert_counts(&incremental);
+    }
+
+    #[test]
+    fn independent_mode() {
+        let mut incremental = IncrementalSplitter::new(SplitterOptions {
+            split_mode: SplitMode::Independent,
+            ..Default::default()
+        });
+        incremental.insert(0, &"word ".repeat(40));
+        assert_counts(&incremental);
+        assert_eq!(incremental.parts(), 2);
+    }
+}
@@ -19,24 +19,46 @@
 //! use sms_splitter::SplitSms;
 //!
 //! fn main(){
-//!     let info = SplitSms::default().split("Hello World!".to_string());
+//!     let info = SplitSms::default().split(String::from("Hello World!"));
 //!     println!("{:#?}", info);
 //! }
 //! ```
 //! <!-- out put -->
 //! ```text
 //! SplitSmsResult {
-//!     character_set: "GSM",
+//!     character_set: Gsm7,
 //!     parts: [
 //!         SplitterPart {
 //!             content: "Hello World!",
 //!             length: 12,
 //!             bytes: 12,
+//!             offsets: PartOffsets {
+//!                 byte_start: 0,
+//!                 byte_end: 12,
+//!                 char_start: 0,
+//!                 char_end: 12,
+//!                 utf16_start: 0,
+//!                 utf16_end: 12,
+//!             },
+//!             counts: Counts {
+//!                 graphemes: 12,
+//!                 scalars: 12,
+//!                 utf16_units: 12,
+//!                 encoded_units: 12,
+//!             },
 //!         },
 //!     ],
 //!     bytes: 12,
 //!     length: 12,
+//!     counts: Counts {
+//!         graphemes: 12,
+//!         scalars: 12,
+//!         utf16_units: 12,
+//!         encoded_units: 12,
+//!     },
 //!     remaining_in_part: 148,
+//!     exceeds_max_parts: false,
+//!     efficiency_loss: None,
 //! }
 //! ```
 //!
@@ -44,19 +66,58 @@
 //!
 //! A lot of the code in this package was based on Codesleuth [`split-sms`](https://github.com/Codesleuth/split-sms).
 //!
+#![no_std]
+
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+#[cfg(feature = "std")]
+pub mod at_command;
+pub mod batch;
+#[cfg(all(feature = "std", feature = "serde_json"))]
+pub mod carrier_lookup;
+pub mod carrier_profile;
+pub mod character_set;
+pub mod ems;
+mod encoded_char;
 pub mod gsm_splitter;
 pub mod gsm_validator;
+pub mod incremental;
+pub mod part_numbering;
+pub mod pdu;
+#[cfg(all(feature = "std", feature = "serde_json"))]
+pub mod pricing;
+pub mod split_str;
+pub mod splitter_error;
 pub mod splitter_options;
 pub mod splitter_result;
+pub mod suggestions;
+pub mod template;
+pub mod truncate;
 pub mod unicode_splitter;
+pub mod word_splitter;
 // use
-use splitter_options::SplitterOptions;
-use splitter_result::{SplitterPart, SplitterResult};
-use serde::ser::SerializeStruct;
+use alloc::string::String;
+#[cfg(any(feature = "serde_json", test))]
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use carrier_profile::CarrierProfile;
+use character_set::{CharacterSet, NationalTables};
+use part_numbering::NumberedSplitter;
+use splitter_error::SplitError;
+use splitter_options::{
+    Encoding, MaxPartsPolicy, SplitMode, SplitterOptions, MAX_CONCATENATED_PARTS,
+};
+use splitter_result::{Counts, CursorPosition, OffsetUnit, SplitterPart, SplitterResult};
+use word_splitter::{EfficiencyLoss, WordSplitter};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
 pub struct SplitSms {
     options: SplitterOptions,
+    profile: CarrierProfile,
 }
 
 impl Default for SplitSms {
@@ -67,12 +128,24 @@ impl Default for SplitSms {
 
 impl SplitSms {
     pub fn new(options: SplitterOptions) -> SplitSms {
-        SplitSms { options }
+        SplitSms::with_profile(options, CarrierProfile::default())
+    }
+
+    /// Splits with the counting rules of a carrier instead of the GSM defaults.
+    pub fn with_profile(options: SplitterOptions, profile: CarrierProfile) -> SplitSms {
+        SplitSms { options, profile }
+    }
+
+    // shift tables only apply when the carrier supports them
+    fn splitter_options(&self) -> SplitterOptions {
+        let mut options = self.options.clone();
+        options.support_shift_tables &= self.profile.shift_tables;
+        options
     }
 
     fn calculate_remaining(
         &self,
-        parts: &Vec<SplitterPart>,
+        parts: &[SplitterPart],
         single_bytes: usize,
         multi_bytes: usize,
         char_bytes: usize,
@@ -81,68 +154,202 @@ impl SplitSms {
         if parts.len() == 1 {
             max = single_bytes;
         }
-        (max - parts[parts.len() - 1].bytes) / char_bytes
+        max.saturating_sub(parts[parts.len() - 1].bytes) / char_bytes
+    }
+
+    /// Checks the carrier profile is usable and `max_parts` allows at least one part.
+    pub fn validate(&self) -> Result<(), SplitError> {
+        self.profile.validate()?;
+        if self.options.max_parts == Some(0) {
+            return Err(SplitError::InvalidOptions(String::from(
+                "max_parts must be at least 1",
+            )));
+        }
+        Ok(())
+    }
+
+    // at least one part, as `split` cannot fail on options `validate` rejects
+    fn max_parts(&self) -> usize {
+        self.options
+            .max_parts
+            .unwrap_or(MAX_CONCATENATED_PARTS)
+            .min(self.profile.max_parts)
+            .clamp(1, MAX_CONCATENATED_PARTS)
+    }
+
+    fn validate_message(&self, message: &str) -> bool {
+        if self.options.support_shift_tables && self.profile.shift_tables {
+            return gsm_validator::GsmValidator::new().validate_str_with_shift_table(message);
+        }
+        gsm_validator::GsmValidator::new().validate_str(message)
+    }
+
+    /// Characters of `message` outside the GSM alphabet, once each in order of appearance.
+    /// These are the characters that make it Unicode, or that a forced GSM encoding replaces.
+    pub fn non_gsm_characters(&self, message: &str) -> Vec<char> {
+        let mut chars = Vec::new();
+        for c in message.chars() {
+            if !chars.contains(&c) && !self.validate_message(c.encode_utf8(&mut [0; 4])) {
+                chars.push(c);
+            }
+        }
+        chars
     }
 
-    fn validate_message(&self, message: String) -> bool {
-        if self.options.support_shift_tables {
-            return gsm_validator::GsmValidator::new().validate_message_with_shift_table(message);
+    // whether `message` goes out in GSM, unless the options force an encoding
+    fn is_gsm(&self, message: &str) -> bool {
+        match self.options.encoding {
+            Encoding::Auto => self.validate_message(message),
+            Encoding::Gsm(_) => true,
+            Encoding::Ucs2 => false,
         }
-        gsm_validator::GsmValidator::new().validate_message(message)
     }
 
+    // shift tables only count as used for characters the default alphabet does not have
+    fn character_set(&self, message: &str, is_gsm: bool) -> CharacterSet {
+        if !is_gsm {
+            return CharacterSet::Ucs2;
+        }
+        if self.splitter_options().support_shift_tables
+            && message.chars().any(character_set::is_national)
+        {
+            return CharacterSet::Gsm7National(NationalTables::for_message(message));
+        }
+        CharacterSet::Gsm7
+    }
+
+    /// Splits a message, applying `max_parts` with the configured policy. `split` cannot fail,
+    /// so [`MaxPartsPolicy::Error`] only flags the result here, characters that cannot be
+    /// decoded are replaced and a numbering counter that fills its part still gets a character
+    /// of text; use [`SplitSms::try_split`] to get the error.
     pub fn split(&self, message: String) -> SplitSmsResult {
-        let is_gsm = self.validate_message(message.clone());
-        let split_result: SplitterResult;
-        let single_bytes: usize;
-        let multi_bytes: usize;
-        let char_bytes: usize;
-        let character_set: String;
-        if is_gsm {
-            split_result = gsm_splitter::GsmSplitter::new(SplitterOptions::new(
-                self.options.support_shift_tables,
-                self.options.summary,
-            ))
-            .split(message);
-            single_bytes = 160;
-            multi_bytes = 153;
-            char_bytes = 1;
-            character_set = "GSM".to_string();
+        self.split_lossy(message).0
+    }
+
+    // the split with the first decoding error of the splitter
+    fn split_lossy(&self, message: String) -> (SplitSmsResult, Option<SplitError>) {
+        let numbering = self.options.part_numbering.clone();
+        let is_gsm = self.is_gsm(&message)
+            && numbering.as_ref().is_none_or(|n| self.is_gsm(&n.pattern));
+        let independent = self.options.split_mode == SplitMode::Independent;
+        let options = self.splitter_options();
+        let profile = self.profile.clone();
+        let capacity = self.profile.capacity(is_gsm);
+        let char_bytes = if is_gsm { 1 } else { 2 };
+        let character_set = self.character_set(&message, is_gsm);
+        let single_bytes = capacity.single;
+        let multi_bytes = if independent {
+            capacity.single
+        } else {
+            capacity.multi
+        };
+        let (mut split_result, error): (SplitterResult, _) = if let Some(numbering) = numbering {
+            let splitter =
+                NumberedSplitter::with_profile(options.clone(), numbering, profile, is_gsm);
+            splitter.split_lossy(message.clone())
+        } else if independent {
+            let splitter = WordSplitter::with_profile(options.clone(), profile, is_gsm);
+            (splitter.split(message.clone()), None)
+        } else if is_gsm {
+            gsm_splitter::GsmSplitter::with_profile(
+                SplitterOptions {
+                    encoding: options.encoding,
+                    ..SplitterOptions::new(options.support_shift_tables, options.summary)
+                },
+                profile,
+            )
+            .split_lossy(&message)
         } else {
-            split_result = unicode_splitter::UnicodeSplitter::new(SplitterOptions::new(
-                self.options.support_shift_tables,
-                self.options.summary,
-            ))
-            .split(message);
-            single_bytes = 140;
-            multi_bytes = 134;
-            char_bytes = 2;
-            character_set = "Unicode".to_string();
+            unicode_splitter::UnicodeSplitter::with_profile(
+                SplitterOptions {
+                    encoding: options.encoding,
+                    ..SplitterOptions::new(options.support_shift_tables, options.summary)
+                },
+                profile,
+            )
+            .split_lossy(&message)
+        };
+        let mut efficiency_loss = None;
+        // a failed split has no loss to report
+        if independent && error.is_none() {
+            efficiency_loss = Some(
+                WordSplitter::with_profile(options, self.profile.clone(), is_gsm)
+                    .efficiency_loss(&message, &split_result.parts),
+            );
+        }
+        let exceeds_max_parts = split_result.parts.len() > self.max_parts();
+        if exceeds_max_parts && self.options.max_parts_policy == MaxPartsPolicy::Truncate {
+            split_result.parts.truncate(self.max_parts());
+            split_result.total_bytes = split_result.parts.iter().map(|p| p.bytes).sum();
+            split_result.total_length = split_result.parts.iter().map(|p| p.length).sum();
         }
         let remaining_in_part =
             self.calculate_remaining(&split_result.parts, single_bytes, multi_bytes, char_bytes);
-        SplitSmsResult::new(
+        let mut result = SplitSmsResult::new(
             character_set,
             split_result.parts,
             split_result.total_bytes,
             split_result.total_length,
             remaining_in_part,
-        )
+        );
+        result.exceeds_max_parts = exceeds_max_parts;
+        result.efficiency_loss = efficiency_loss;
+        (result, error)
+    }
+
+    /// Like [`SplitSms::split`], but fails when the message needs more than `max_parts` parts
+    /// under [`MaxPartsPolicy::Error`], or more than [`MAX_CONCATENATED_PARTS`] under any policy
+    /// but truncation, when a part cannot be decoded, or when [`SplitSms::validate`] fails.
+    pub fn try_split(&self, message: String) -> Result<SplitSmsResult, SplitError> {
+        self.validate()?;
+        let (result, error) = self.split_lossy(message);
+        if let Some(error) = error {
+            return Err(error);
+        }
+        if !result.exceeds_max_parts || self.options.max_parts_policy == MaxPartsPolicy::Truncate
+        {
+            return Ok(result);
+        }
+        if result.parts.len() > MAX_CONCATENATED_PARTS {
+            return Err(SplitError::TooManyParts {
+                parts: result.parts.len(),
+                max_parts: MAX_CONCATENATED_PARTS,
+            });
+        }
+        if self.options.max_parts_policy == MaxPartsPolicy::Error {
+            return Err(SplitError::TooManyParts {
+                parts: result.parts.len(),
+                max_parts: self.max_parts(),
+            });
+        }
+        Ok(result)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SplitSmsResult {
-    pub character_set: String,
+    pub character_set: CharacterSet,
     pub parts: Vec<SplitterPart>,
     pub bytes: usize,
     pub length: usize,
+    /// The counts of the parts summed, see [`Counts`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub counts: Counts,
     pub remaining_in_part: usize,
+    /// The message needed more parts than `max_parts` allows.
+    pub exceeds_max_parts: bool,
+    /// Set in [`SplitMode::Independent`], compares the word boundary split with a hard one.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub efficiency_loss: Option<EfficiencyLoss>,
 }
 
 impl SplitSmsResult {
     pub fn new(
-        character_set: String,
+        character_set: CharacterSet,
         parts: Vec<SplitterPart>,
         bytes: usize,
         length: usize,
@@ -150,58 +357,360 @@ impl SplitSmsResult {
     ) -> SplitSmsResult {
         SplitSmsResult {
             character_set,
+            counts: parts.iter().map(|p| p.counts).sum(),
             parts,
             bytes,
             length,
             remaining_in_part,
+            exceeds_max_parts: false,
+            efficiency_loss: None,
         }
     }
+}
 
-    // to string
-    pub fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl SplitSmsResult {
+    /// The part a cursor in the original message falls in, see [`splitter_result::cursor_position`].
+    pub fn cursor_position(&self, cursor: usize, unit: OffsetUnit) -> Option<CursorPosition> {
+        splitter_result::cursor_position(&self.parts, cursor, unit)
     }
-    
-}
 
-// Serialize
-impl serde::Serialize for SplitSmsResult {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        let mut state = serializer.serialize_struct("SplitSmsResult", 5)?;
-        state.serialize_field("character_set", &self.character_set)?;
-        state.serialize_field("parts", &self.parts)?;
-        state.serialize_field("bytes", &self.bytes)?;
-        state.serialize_field("length", &self.length)?;
-        state.serialize_field("remaining_in_part", &self.remaining_in_part)?;
-        state.end()
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> Result<String, SplitError> {
+        serde_json::to_string(&self).map_err(|e| SplitError::Serialization(e.to_string()))
     }
-}
 
-// SplitterPart Serialize
-impl serde::Serialize for SplitterPart {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        let mut state = serializer.serialize_struct("SplitterPart", 3)?;
-        state.serialize_field("content", &self.content)?;
-        state.serialize_field("length", &self.length)?;
-        state.serialize_field("bytes", &self.bytes)?;
-        state.end()
+    // to string
+    #[cfg(feature = "serde_json")]
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        serde_json::to_string(&self).unwrap()
     }
 }
 
 impl Clone for SplitSmsResult {
     fn clone(&self) -> Self {
         SplitSmsResult {
-            character_set: self.character_set.clone(),
+            character_set: self.character_set,
             parts: self.parts.clone(),
             bytes: self.bytes,
             length: self.length,
+            counts: self.counts,
             remaining_in_part: self.remaining_in_part,
+            exceeds_max_parts: self.exceeds_max_parts,
+            efficiency_loss: self.efficiency_loss,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use splitter_options::Replacement;
+
+    fn limited(max_parts: usize, max_parts_policy: MaxPartsPolicy) -> SplitSms {
+        SplitSms::new(SplitterOptions {
+            max_parts: Some(max_parts),
+            max_parts_policy,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn max_parts_error() {
+        let splitter = limited(2, MaxPartsPolicy::Error);
+        assert!(splitter.try_split("a".repeat(306)).is_ok());
+        assert_eq!(
+            splitter.try_split("a".repeat(307)).unwrap_err(),
+            SplitError::TooManyParts {
+                parts: 3,
+                max_parts: 2
+            }
+        );
+        assert!(splitter.split("a".repeat(307)).exceeds_max_parts);
+    }
+
+    #[test]
+    fn max_parts_truncate() {
+        let result = limited(2, MaxPartsPolicy::Truncate)
+            .try_split("a".repeat(400))
+            .unwrap();
+        assert!(result.exceeds_max_parts);
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.length, 306);
+        assert_eq!(result.bytes, 306);
+        assert_eq!(result.remaining_in_part, 0);
+    }
+
+    #[test]
+    fn max_parts_flag() {
+        let result = limited(2, MaxPartsPolicy::Flag)
+            .try_split("a".repeat(400))
+            .unwrap();
+        assert!(result.exceeds_max_parts);
+        assert_eq!(result.parts.len(), 3);
+    }
+
+    #[test]
+    fn max_parts_zero() {
+        for policy in [MaxPartsPolicy::Error, MaxPartsPolicy::Truncate, MaxPartsPolicy::Flag] {
+            assert!(matches!(
+                limited(0, policy).try_split(String::from("hi")),
+                Err(SplitError::InvalidOptions(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn independent_split_mode() {
+        let splitter = SplitSms::new(SplitterOptions {
+            split_mode: SplitMode::Independent,
+            ..Default::default()
+        });
+        let result = splitter.split("word ".repeat(40));
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.parts[0].length, 160);
+        assert_eq!(result.remaining_in_part, 120);
+        assert_eq!(result.efficiency_loss.unwrap().extra_parts, 0);
+        #[cfg(feature = "serde_json")]
+        assert!(result.to_string().contains("\"efficiency_loss\""));
+        assert!(SplitSms::default().split("word ".repeat(40)).efficiency_loss.is_none());
+    }
+
+    #[test]
+    fn part_numbering() {
+        let splitter = SplitSms::new(SplitterOptions {
+            part_numbering: Some(part_numbering::PartNumbering::default()),
+            ..Default::default()
+        });
+        let result = splitter.split("a".repeat(200));
+        assert_eq!(result.parts[0].content, "(1/2) ".to_string() + &"a".repeat(147));
+        assert_eq!(result.remaining_in_part, 153 - 59);
+        let result = splitter.split("ก".repeat(100));
+        assert_eq!(result.character_set, CharacterSet::Ucs2);
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.parts[1].content, "(2/2) ".to_string() + &"ก".repeat(39));
+    }
+
+    #[test]
+    fn concatenation_limit() {
+        let message = "a".repeat(153 * 255 + 1);
+        let error = limited(300, MaxPartsPolicy::Flag)
+            .try_split(message.clone())
+            .unwrap_err();
+        assert_eq!(
+            error,
+            SplitError::TooManyParts {
+                parts: 256,
+                max_parts: MAX_CONCATENATED_PARTS
+            }
+        );
+        let result = SplitSms::default().split(message);
+        assert!(result.exceeds_max_parts);
+    }
+
+    #[test]
+    fn cdma_profile() {
+        let splitter = SplitSms::with_profile(SplitterOptions::default(), CarrierProfile::cdma());
+        // no concatenation header, every part holds 160 characters
+        let result = splitter.split("a".repeat(200));
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.parts[0].bytes, 160);
+        assert_eq!(result.remaining_in_part, 120);
+        // extension characters count once
+        let result = splitter.split("{}".repeat(80));
+        assert_eq!(result.parts.len(), 1);
+        assert_eq!(result.bytes, 160);
+        let result = splitter.split("ก".repeat(71));
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.parts[0].length, 70);
+    }
+
+    #[test]
+    fn profile_limits() {
+        let profile = CarrierProfile {
+            max_parts: 2,
+            ..CarrierProfile::gsm_16bit_reference()
+        };
+        let splitter = SplitSms::with_profile(SplitterOptions::default(), profile);
+        let result = splitter.split("a".repeat(305));
+        assert_eq!(result.parts[0].bytes, 152);
+        assert_eq!(result.parts.len(), 3);
+        assert!(result.exceeds_max_parts);
+
+        // shift tables are ignored when the carrier does not support them
+        let options = SplitterOptions::new(true, false);
+        let message = String::from("Ş");
+        assert!(SplitSms::new(options.clone())
+            .split(message.clone())
+            .character_set
+            .is_gsm());
+        let result = SplitSms::with_profile(options, CarrierProfile::cdma()).split(message);
+        assert_eq!(result.character_set, CharacterSet::Ucs2);
+    }
+
+    #[test]
+    fn non_gsm_characters() {
+        let splitter = SplitSms::default();
+        assert!(splitter.non_gsm_characters("Hello {World}!").is_empty());
+        assert_eq!(
+            splitter.non_gsm_characters("Ş Ş \u{2019}"),
+            ['Ş', '\u{2019}']
+        );
+        let splitter = SplitSms::new(SplitterOptions::new(true, false));
+        assert_eq!(splitter.non_gsm_characters("Ş Ş \u{2019}"), ['\u{2019}']);
+    }
+
+    #[test]
+    fn forced_encoding() {
+        let forced = |encoding| {
+            SplitSms::new(SplitterOptions {
+                encoding,
+                ..Default::default()
+            })
+        };
+        let result = forced(Encoding::Ucs2).split(String::from("Hello"));
+        assert_eq!(result.character_set, CharacterSet::Ucs2);
+        assert_eq!(result.bytes, 10);
+        assert_eq!(result.remaining_in_part, 65);
+
+        // lookalikes keep one character for one, so offsets still match the message
+        let message = "it\u{2019}s \u{201c}ok\u{201d} 😀 ".repeat(20);
+        let splitter = forced(Encoding::Gsm(Replacement::Lookalike));
+        let result = splitter.split(message.clone());
+        assert_eq!(result.character_set, CharacterSet::Gsm7);
+        assert_eq!(result.length, 240);
+        assert_eq!(result.parts.len(), 2);
+        assert!(result.parts[0].content.starts_with("it's \"ok\" ? it's"));
+        assert_eq!(result.parts[1].offsets.char_start, 153);
+        assert_eq!(result.parts[1].offsets.char_end, message.chars().count());
+        assert_eq!(splitter.split_str(&message).parts.len(), 2);
+
+        let splitter = forced(Encoding::Gsm(Replacement::Char('€')));
+        let result = splitter.split(String::from("á😀"));
+        assert_eq!(result.parts[0].content, "€€");
+        assert_eq!(result.bytes, 4);
+        // a replacement outside the alphabet falls back to a space
+        let result = forced(Encoding::Gsm(Replacement::Char('á'))).split(String::from("ó"));
+        assert_eq!(result.parts[0].content, " ");
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn result_round_trip() {
+        let result = SplitSms::default().split("😀".repeat(40));
+        let json = result.to_json().unwrap();
+        let read: SplitSmsResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, result);
+        assert_eq!(read.character_set, CharacterSet::Ucs2);
+        // results serialized before counts and offsets still read
+        let read: SplitSmsResult = serde_json::from_str(
+            r#"{"character_set":"gsm7","parts":[{"content":"hi","length":2,"bytes":2}],
+                "bytes":2,"length":2,"remaining_in_part":158,"exceeds_max_parts":false}"#,
+        )
+        .unwrap();
+        assert_eq!(read.parts[0].content, "hi");
+        assert_eq!(read.efficiency_loss, None);
+        assert_eq!(read.character_set, CharacterSet::Gsm7);
+        // the names of older versions are kept when writing
+        let read: SplitSmsResult = serde_json::from_str(
+            r#"{"character_set":"GSM","parts":[],"bytes":0,"length":0,
+                "remaining_in_part":160,"exceeds_max_parts":false}"#,
+        )
+        .unwrap();
+        assert_eq!(read.character_set, CharacterSet::Gsm7);
+        assert_eq!(serde_json::to_string(&CharacterSet::Gsm7).unwrap(), r#""GSM""#);
+        assert_eq!(serde_json::to_string(&CharacterSet::Ucs2).unwrap(), r#""Unicode""#);
+    }
+
+    #[test]
+    fn national_tables() {
+        let splitter = SplitSms::new(SplitterOptions::new(true, false));
+        let result = splitter.split(String::from("Ağaç"));
+        assert_eq!(
+            result.character_set,
+            CharacterSet::Gsm7National(NationalTables {
+                locking: Some(character_set::NationalLanguage::Turkish),
+                single: None,
+            })
+        );
+        #[cfg(feature = "serde_json")]
+        assert!(result.to_json().unwrap().contains(
+            r#""character_set":{"gsm7_national":{"locking":"turkish","single":null}}"#
+        ));
+        assert_eq!(
+            splitter.split(String::from("Hello")).character_set,
+            CharacterSet::Gsm7
+        );
+    }
+
+    #[test]
+    fn invalid_profile() {
+        let profile = CarrierProfile {
+            unicode: carrier_profile::Capacity {
+                single: 2,
+                multi: 2,
+            },
+            ..CarrierProfile::gsm()
+        };
+        let splitter = SplitSms::with_profile(SplitterOptions::default(), profile);
+        assert!(matches!(
+            splitter.try_split(String::from("😀")),
+            Err(SplitError::InvalidOptions(_))
+        ));
+        // split still returns a result
+        assert_eq!(splitter.split(String::from("😀😀")).parts.len(), 2);
+    }
+
+    #[test]
+    fn numbering_over_capacity() {
+        let splitter = SplitSms::new(SplitterOptions {
+            part_numbering: Some(part_numbering::PartNumbering::new(
+                "-".repeat(153) + "{part}",
+                part_numbering::NumberingPosition::Suffix,
+            )),
+            ..Default::default()
+        });
+        assert!(matches!(
+            splitter.try_split("a".repeat(200)),
+            Err(SplitError::InvalidOptions(_))
+        ));
+        assert!(splitter.split("a".repeat(200)).parts[0].bytes > 153);
+    }
+
+    #[test]
+    fn independent_over_capacity() {
+        let splitter = SplitSms::new(SplitterOptions {
+            split_mode: SplitMode::Independent,
+            part_numbering: Some(part_numbering::PartNumbering::new(
+                "-".repeat(160) + "{part}",
+                part_numbering::NumberingPosition::Suffix,
+            )),
+            ..Default::default()
+        });
+        assert!(splitter.try_split("word ".repeat(40)).is_err());
+        assert_eq!(splitter.split("word ".repeat(40)).efficiency_loss, None);
+        let profile = CarrierProfile {
+            gsm: carrier_profile::Capacity {
+                single: 0,
+                multi: 0,
+            },
+            ..CarrierProfile::gsm()
+        };
+        let options = SplitterOptions {
+            split_mode: SplitMode::Independent,
+            ..Default::default()
+        };
+        let result = SplitSms::with_profile(options, profile).split(String::from("a b"));
+        assert_eq!(result.efficiency_loss.unwrap().unused_characters, 0);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn to_json() {
+        let json = SplitSms::default()
+            .try_split(String::from("hi"))
+            .and_then(|result| result.to_json())
+            .unwrap();
+        assert!(json.starts_with(r#"{"character_set":"GSM""#));
+    }
+}
@@ -0,0 +1,303 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    carrier_profile::CarrierProfile,
+    encoded_char::{encode_chars, fill, word_break, EncodedChar},
+    splitter_error::SplitError,
+    splitter_options::{SplitMode, SplitterOptions},
+    splitter_result::{SplitterPart, SplitterResult},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum NumberingPosition {
+    #[default]
+    Prefix,
+    Suffix,
+}
+
+/// A counter written into every part of a multipart message, for routes that cannot
+/// concatenate. `{part}` and `{total}` in the pattern are replaced by the part number and the
+/// number of parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PartNumbering {
+    pub pattern: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub position: NumberingPosition,
+}
+
+impl Default for PartNumbering {
+    fn default() -> Self {
+        PartNumbering::new("({part}/{total}) ".to_string(), NumberingPosition::Prefix)
+    }
+}
+
+impl PartNumbering {
+    pub fn new(pattern: String, position: NumberingPosition) -> PartNumbering {
+        PartNumbering { pattern, position }
+    }
+
+    pub fn render(&self, part: usize, total: usize) -> String {
+        self.pattern
+            .replace("{part}", &part.to_string())
+            .replace("{total}", &total.to_string())
+    }
+}
+
+/// Splits with the counter counted inside each part's capacity. A message that fits in one part
+/// gets no counter.
+pub struct NumberedSplitter {
+    options: SplitterOptions,
+    numbering: PartNumbering,
+    profile: CarrierProfile,
+    is_gsm: bool,
+}
+
+impl NumberedSplitter {
+    pub fn new(
+        options: SplitterOptions,
+        numbering: PartNumbering,
+        is_gsm: bool,
+    ) -> NumberedSplitter {
+        NumberedSplitter::with_profile(options, numbering, CarrierProfile::default(), is_gsm)
+    }
+
+    pub fn with_profile(
+        options: SplitterOptions,
+        numbering: PartNumbering,
+        profile: CarrierProfile,
+        is_gsm: bool,
+    ) -> NumberedSplitter {
+        NumberedSplitter {
+            options,
+            numbering,
+            profile,
+            is_gsm,
+        }
+    }
+
+    fn single_capacity(&self) -> usize {
+        self.profile.capacity(self.is_gsm).single
+    }
+
+    fn multi_capacity(&self) -> usize {
+        match self.options.split_mode {
+            SplitMode::Independent => self.single_capacity(),
+            SplitMode::Concatenated => self.profile.capacity(self.is_gsm).multi,
+        }
+    }
+
+    fn counter_bytes(&self, part: usize, total: usize) -> usize {
+        encode_chars(
+            &self.numbering.render(part, total),
+            self.is_gsm,
+            &self.options,
+            &self.profile,
+        )
+        .iter()
+        .map(|c| c.bytes)
+        .sum()
+    }
+
+    // character ranges of the parts when the counters say there are `total` of them
+    fn pack(&self, chars: &[EncodedChar], total: usize) -> Vec<(usize, usize)> {
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let counter = self.counter_bytes(ranges.len() + 1, total);
+            let capacity = self.multi_capacity().saturating_sub(counter);
+            let mut end = fill(chars, start, capacity);
+            if self.options.split_mode == SplitMode::Independent {
+                end = word_break(chars, start, end);
+            }
+            ranges.push((start, end));
+            start = end;
+        }
+        ranges
+    }
+
+    /// Splits a message. A counter that leaves no room for text still gets a character of it,
+    /// over the capacity of the part; [`SplitSms::try_split`](crate::SplitSms::try_split)
+    /// returns an error for it instead.
+    pub fn split(&self, message: String) -> SplitterResult {
+        self.split_lossy(message).0
+    }
+
+    // the split with an error when a part is over capacity
+    pub(crate) fn split_lossy(&self, message: String) -> (SplitterResult, Option<SplitError>) {
+        let chars = encode_chars(&message, self.is_gsm, &self.options, &self.profile);
+        let total_bytes: usize = chars.iter().map(|c| c.bytes).sum();
+        if total_bytes <= self.single_capacity() {
+            let content = if self.options.summary {
+                String::new()
+            } else {
+                chars.iter().map(|c| c.content).collect()
+            };
+            let mut result = SplitterResult {
+                parts: vec![SplitterPart::new(content, chars.len(), total_bytes)],
+                total_length: chars.len(),
+                total_bytes,
+            };
+            result.set_offsets(&message, [chars.len()]);
+            return (result, None);
+        }
+
+        // Longer counters can push text into a new part, which can lengthen the counters again.
+        // The part count only grows with the assumed total, so iterating from below settles on
+        // the smallest total that is consistent with its own counters.
+        let mut total = 2;
+        let mut ranges = self.pack(&chars, total);
+        while ranges.len() > total {
+            total = ranges.len();
+            ranges = self.pack(&chars, total);
+        }
+        // word breaks can leave fewer parts than assumed, the counters then only get shorter
+        let total = ranges.len();
+
+        let mut parts = Vec::new();
+        for (i, (start, end)) in ranges.iter().enumerate() {
+            let counter = self.numbering.render(i + 1, total);
+            let counter_chars = encode_chars(&counter, self.is_gsm, &self.options, &self.profile);
+            let text = &chars[*start..*end];
+            let bytes = text
+                .iter()
+                .chain(counter_chars.iter())
+                .map(|c| c.bytes)
+                .sum();
+            let mut content = String::new();
+            if !self.options.summary {
+                let counter: String = counter_chars.iter().map(|c| c.content).collect();
+                let text: String = text.iter().map(|c| c.content).collect();
+                content = match self.numbering.position {
+                    NumberingPosition::Prefix => counter + &text,
+                    NumberingPosition::Suffix => text + &counter,
+                };
+            }
+            parts.push(SplitterPart::new(
+                content,
+                text.len() + counter_chars.len(),
+                bytes,
+            ));
+        }
+        let mut result = SplitterResult {
+            total_length: parts.iter().map(|p| p.length).sum(),
+            total_bytes: parts.iter().map(|p| p.bytes).sum(),
+            parts,
+        };
+        // offsets cover the message text, without the counters
+        result.set_offsets(&message, ranges.iter().map(|(start, end)| end - start));
+        let error = result
+            .parts
+            .iter()
+            .position(|part| part.bytes > self.multi_capacity())
+            .map(|i| {
+                SplitError::InvalidOptions(format!(
+                    "the counter of part {} leaves no room for its text",
+                    i + 1
+                ))
+            });
+        (result, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn splitter(split_mode: SplitMode, numbering: PartNumbering, is_gsm: bool) -> NumberedSplitter {
+        let options = SplitterOptions {
+            split_mode,
+            ..Default::default()
+        };
+        NumberedSplitter::new(options, numbering, is_gsm)
+    }
+
+    #[test]
+    fn single_part_has_no_counter() {
+        let result = splitter(SplitMode::Concatenated, PartNumbering::default(), true)
+            .split("a".repeat(160));
+        assert_eq!(result.parts.len(), 1);
+        assert_eq!(result.parts[0].content, "a".repeat(160));
+    }
+
+    #[test]
+    fn prefix_counts_inside_capacity() {
+        let result = splitter(SplitMode::Concatenated, PartNumbering::default(), true)
+            .split("a".repeat(200));
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(
+            result.parts[0].content,
+            "(1/2) ".to_string() + &"a".repeat(147)
+        );
+        assert_eq!(result.parts[0].bytes, 153);
+        assert_eq!(
+            result.parts[1].content,
+            "(2/2) ".to_string() + &"a".repeat(53)
+        );
+        assert_eq!(result.total_length, 212);
+    }
+
+    #[test]
+    fn counter_over_capacity() {
+        let numbering = PartNumbering::new("#".repeat(160) + "{part} ", NumberingPosition::Prefix);
+        let (result, error) =
+            splitter(SplitMode::Concatenated, numbering, true).split_lossy("a".repeat(200));
+        assert!(result.parts.iter().all(|part| part.bytes > 153));
+        assert_eq!(
+            error,
+            Some(SplitError::InvalidOptions(
+                "the counter of part 1 leaves no room for its text".to_string()
+            ))
+        );
+        // a counter that fits leaves no error
+        let (_, error) = splitter(SplitMode::Concatenated, PartNumbering::default(), true)
+            .split_lossy("a".repeat(200));
+        assert_eq!(error, None);
+    }
+
+    #[test]
+    fn suffix() {
+        let numbering =
+            PartNumbering::new(" [{part}/{total}]".to_string(), NumberingPosition::Suffix);
+        let result = splitter(SplitMode::Independent, numbering, true).split("a".repeat(200));
+        // the brackets are extension table characters, two septets each
+        assert_eq!(result.parts[0].content, "a".repeat(152) + " [1/2]");
+        assert_eq!(result.parts[0].bytes, 160);
+    }
+
+    #[test]
+    fn total_digits_change_part_count() {
+        // 9 parts of 154 characters hold 1386, one more needs a tenth part and the longer
+        // "(n/10) " counters
+        let result = splitter(SplitMode::Independent, PartNumbering::default(), true)
+            .split("a".repeat(1387));
+        assert_eq!(result.parts.len(), 10);
+        assert!(result.parts[9].content.starts_with("(10/10) "));
+        assert!(result.parts.iter().all(|p| p.bytes <= 160));
+        let text: usize = result.parts.iter().map(|p| p.length).sum::<usize>()
+            - (9 * "(1/10) ".len() + "(10/10) ".len());
+        assert_eq!(text, 1387);
+        assert_eq!(result.parts[0].length, 160);
+    }
+
+    #[test]
+    fn unicode_counter() {
+        let result = splitter(SplitMode::Concatenated, PartNumbering::default(), false)
+            .split("ก".repeat(100));
+        assert_eq!(
+            result.parts[0].content,
+            "(1/2) ".to_string() + &"ก".repeat(61)
+        );
+        assert_eq!(result.parts[0].bytes, 134);
+    }
+}
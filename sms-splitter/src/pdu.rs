@@ -0,0 +1,469 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::{
+    carrier_profile::UdhStyle, character_set::CharacterSet,
+    splitter_options::MAX_CONCATENATED_PARTS, SplitSmsResult,
+};
+
+// GSM 03.38 default alphabet, indexed by septet value. 0x1B is the escape to the extension table.
+const GSM_DEFAULT_ALPHABET: [char; 128] = [
+    '@', '£', '$', '¥', 'è', 'é', 'ù', 'ì', 'ò', 'Ç', '\n', 'Ø', 'ø', '\r', 'Å', 'å', //
+    'Δ', '_', 'Φ', 'Γ', 'Λ', 'Ω', 'Π', 'Ψ', 'Σ', 'Θ', 'Ξ', '\u{1b}', 'Æ', 'æ', 'ß', 'É', //
+    ' ', '!', '"', '#', '¤', '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/', //
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ':', ';', '<', '=', '>', '?', //
+    '¡', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', //
+    'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 'Ä', 'Ö', 'Ñ', 'Ü', '§', //
+    '¿', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', //
+    'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', 'ä', 'ö', 'ñ', 'ü', 'à', //
+];
+
+// GSM 03.38 extension table, as (septet following the escape, character)
+const GSM_EXTENSION_TABLE: [(u8, char); 10] = [
+    (0x0A, '\u{0c}'),
+    (0x14, '^'),
+    (0x28, '{'),
+    (0x29, '}'),
+    (0x2F, '\\'),
+    (0x3C, '['),
+    (0x3D, '~'),
+    (0x3E, ']'),
+    (0x40, '|'),
+    (0x65, '€'),
+];
+
+const GSM_ESCAPE: u8 = 0x1B;
+// TP-MTI = SMS-SUBMIT, TP-VPF = relative
+const FIRST_OCTET_SUBMIT: u8 = 0x11;
+// TP-UDHI
+const FIRST_OCTET_UDHI: u8 = 0x40;
+const MAX_USER_DATA_OCTETS: usize = 140;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataCoding {
+    Gsm7,
+    Ucs2,
+}
+
+impl DataCoding {
+    /// The TP-DCS octet for this coding (default class, uncompressed).
+    pub fn dcs(&self) -> u8 {
+        match self {
+            DataCoding::Gsm7 => 0x00,
+            DataCoding::Ucs2 => 0x08,
+        }
+    }
+
+    /// GSM 7-bit for the default alphabet, UCS-2 for anything but GSM. Messages needing the
+    /// national language shift tables cannot be encoded, split them without
+    /// `support_shift_tables` to send them as UCS-2.
+    pub fn from_character_set(character_set: CharacterSet) -> Result<DataCoding, PduError> {
+        match character_set {
+            CharacterSet::Gsm7National(_) => Err(PduError::NationalShiftTables),
+            _ if character_set.is_gsm() => Ok(DataCoding::Gsm7),
+            _ => Ok(DataCoding::Ucs2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PduError {
+    /// The character has no septet in the GSM default alphabet or its extension table.
+    UnencodableCharacter(char),
+    /// The part does not fit in a single TPDU once the user data header is added.
+    PartTooLong(usize),
+    /// The destination address contains something other than digits and a leading `+`.
+    InvalidAddress(String),
+    /// The result was produced with `summary` enabled and carries no part content.
+    MissingContent,
+    /// More parts than a concatenation header can number.
+    TooManyParts(usize),
+    /// The message needs the national language shift tables, which are not encoded.
+    NationalShiftTables,
+}
+
+impl core::fmt::Display for PduError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PduError::UnencodableCharacter(c) => {
+                write!(f, "character {:?} has no GSM 7-bit encoding", c)
+            }
+            PduError::PartTooLong(part) => write!(f, "part {} does not fit in one PDU", part),
+            PduError::InvalidAddress(address) => write!(f, "invalid address {:?}", address),
+            PduError::MissingContent => write!(f, "split result has no part content"),
+            PduError::TooManyParts(parts) => write!(
+                f,
+                "{} parts, concatenation supports at most {}",
+                parts, MAX_CONCATENATED_PARTS
+            ),
+            PduError::NationalShiftTables => {
+                write!(f, "national language shift tables cannot be encoded")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PduError {}
+
+/// A user data header information element (TS 23.040 9.2.3.24).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InformationElement {
+    pub identifier: u8,
+    pub data: Vec<u8>,
+}
+
+impl InformationElement {
+    pub fn new(identifier: u8, data: Vec<u8>) -> InformationElement {
+        InformationElement { identifier, data }
+    }
+
+    /// Concatenated short message, 8-bit reference number.
+    pub fn concatenation(reference: u8, total: u8, sequence: u8) -> InformationElement {
+        InformationElement::new(0x00, vec![reference, total, sequence])
+    }
+
+    /// Concatenated short message, 16-bit reference number.
+    pub fn concatenation_16bit(reference: u16, total: u8, sequence: u8) -> InformationElement {
+        let [high, low] = reference.to_be_bytes();
+        InformationElement::new(0x08, vec![high, low, total, sequence])
+    }
+
+    /// Octets taken by this element in the header, identifier and length included.
+    pub fn len(&self) -> usize {
+        2 + self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PduOptions {
+    /// Destination number, digits with an optional leading `+` for international format.
+    pub destination: String,
+    /// Reference number shared by every part of a concatenated message, its low byte in 8-bit
+    /// headers.
+    pub reference: u16,
+    /// Relative TP-VP octet, `0xA7` is 24 hours.
+    pub validity_period: u8,
+    /// Concatenation header of the parts, the `udh` of the profile the message was split with.
+    pub udh: UdhStyle,
+}
+
+impl PduOptions {
+    pub fn new(destination: String, reference: u16) -> PduOptions {
+        PduOptions {
+            destination,
+            reference,
+            validity_period: 0xA7,
+            udh: UdhStyle::default(),
+        }
+    }
+}
+
+/// An SMS-SUBMIT PDU prefixed with an empty SMSC field, ready for `AT+CMGS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmitPdu {
+    pub bytes: Vec<u8>,
+}
+
+impl SubmitPdu {
+    /// The `AT+CMGS` length argument: octets of the TPDU, excluding the SMSC field.
+    pub fn tpdu_length(&self) -> usize {
+        self.bytes.len() - 1
+    }
+
+    pub fn to_hex(&self) -> String {
+        to_hex(&self.bytes)
+    }
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// Maps a character to its septets, two of them for extension table characters.
+pub fn encode_gsm_character(c: char) -> Option<Vec<u8>> {
+    if c != '\u{1b}' {
+        if let Some(septet) = GSM_DEFAULT_ALPHABET.iter().position(|e| *e == c) {
+            return Some(vec![septet as u8]);
+        }
+    }
+    GSM_EXTENSION_TABLE
+        .iter()
+        .find(|(_, e)| *e == c)
+        .map(|(septet, _)| vec![GSM_ESCAPE, *septet])
+}
+
+pub fn encode_gsm(message: &str) -> Result<Vec<u8>, PduError> {
+    let mut septets = Vec::new();
+    for c in message.chars() {
+        match encode_gsm_character(c) {
+            Some(mut encoded) => septets.append(&mut encoded),
+            None => return Err(PduError::UnencodableCharacter(c)),
+        }
+    }
+    Ok(septets)
+}
+
+pub fn encode_ucs2(message: &str) -> Vec<u8> {
+    message
+        .encode_utf16()
+        .flat_map(|unit| unit.to_be_bytes())
+        .collect()
+}
+
+/// Packs septets LSB first, after `fill_bits` zero bits used to align with a preceding header.
+pub fn pack_septets(septets: &[u8], fill_bits: usize) -> Vec<u8> {
+    let mut packed = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut buffered = fill_bits;
+    for septet in septets {
+        buffer |= ((*septet & 0x7F) as u32) << buffered;
+        buffered += 7;
+        while buffered >= 8 {
+            packed.push(buffer as u8);
+            buffer >>= 8;
+            buffered -= 8;
+        }
+    }
+    if buffered > 0 {
+        packed.push(buffer as u8);
+    }
+    packed
+}
+
+/// TP-DA: digit count, type of address and swapped semi-octets.
+pub fn encode_address(address: &str) -> Result<Vec<u8>, PduError> {
+    let international = address.starts_with('+');
+    let digits = address.trim_start_matches('+');
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(PduError::InvalidAddress(address.to_string()));
+    }
+    let mut encoded = vec![digits.len() as u8, if international { 0x91 } else { 0x81 }];
+    let digits = digits.as_bytes();
+    for pair in digits.chunks(2) {
+        let low = pair[0] - b'0';
+        let high = if pair.len() == 2 {
+            pair[1] - b'0'
+        } else {
+            0x0F
+        };
+        encoded.push(high << 4 | low);
+    }
+    Ok(encoded)
+}
+
+/// Builds one SMS-SUBMIT PDU carrying `content` and the given header elements.
+pub fn submit_pdu(
+    content: &str,
+    coding: DataCoding,
+    header: &[InformationElement],
+    options: &PduOptions,
+) -> Result<SubmitPdu, PduError> {
+    let mut udh = Vec::new();
+    if !header.is_empty() {
+        udh.push(header.iter().map(|e| e.len()).sum::<usize>() as u8);
+        for element in header {
+            udh.push(element.identifier);
+            udh.push(element.data.len() as u8);
+            udh.extend_from_slice(&element.data);
+        }
+    }
+    let (user_data_length, user_data) = match coding {
+        DataCoding::Gsm7 => {
+            let septets = encode_gsm(content)?;
+            let header_septets = (udh.len() * 8).div_ceil(7);
+            let fill_bits = header_septets * 7 - udh.len() * 8;
+            let mut user_data = udh.clone();
+            user_data.append(&mut pack_septets(&septets, fill_bits));
+            (header_septets + septets.len(), user_data)
+        }
+        DataCoding::Ucs2 => {
+            let mut user_data = udh.clone();
+            user_data.append(&mut encode_ucs2(content));
+            (user_data.len(), user_data)
+        }
+    };
+    if user_data.len() > MAX_USER_DATA_OCTETS {
+        return Err(PduError::PartTooLong(user_data.len()));
+    }
+    let mut first_octet = FIRST_OCTET_SUBMIT;
+    if !udh.is_empty() {
+        first_octet |= FIRST_OCTET_UDHI;
+    }
+    // empty SMSC field, first octet, TP-MR
+    let mut bytes = vec![0x00, first_octet, 0x00];
+    bytes.append(&mut encode_address(&options.destination)?);
+    bytes.push(0x00); // TP-PID
+    bytes.push(coding.dcs());
+    bytes.push(options.validity_period);
+    bytes.push(user_data_length as u8);
+    bytes.extend_from_slice(&user_data);
+    Ok(SubmitPdu { bytes })
+}
+
+/// Builds the PDUs for every part of a split message, adding a concatenation header of the
+/// `udh` style when there is more than one part.
+pub fn submit_pdus(
+    result: &SplitSmsResult,
+    options: &PduOptions,
+) -> Result<Vec<SubmitPdu>, PduError> {
+    submit_pdus_with_elements(result, &[], options)
+}
+
+/// Like [`submit_pdus`], appending `elements[i]` to the header of part `i`.
+pub fn submit_pdus_with_elements(
+    result: &SplitSmsResult,
+    elements: &[Vec<InformationElement>],
+    options: &PduOptions,
+) -> Result<Vec<SubmitPdu>, PduError> {
+    let coding = DataCoding::from_character_set(result.character_set)?;
+    let total = result.parts.len();
+    if total > MAX_CONCATENATED_PARTS {
+        return Err(PduError::TooManyParts(total));
+    }
+    let mut pdus = Vec::new();
+    for (i, part) in result.parts.iter().enumerate() {
+        if part.content.is_empty() && part.length > 0 {
+            return Err(PduError::MissingContent);
+        }
+        let mut header = Vec::new();
+        let (total, sequence) = (total as u8, (i + 1) as u8);
+        match options.udh {
+            _ if total == 1 => {}
+            UdhStyle::None => {}
+            UdhStyle::Concatenated8Bit => header.push(InformationElement::concatenation(
+                options.reference as u8,
+                total,
+                sequence,
+            )),
+            UdhStyle::Concatenated16Bit => header.push(InformationElement::concatenation_16bit(
+                options.reference,
+                total,
+                sequence,
+            )),
+        }
+        if let Some(part_elements) = elements.get(i) {
+            header.extend_from_slice(part_elements);
+        }
+        let pdu = submit_pdu(&part.content, coding, &header, options).map_err(|e| match e {
+            PduError::PartTooLong(_) => PduError::PartTooLong(i + 1),
+            e => e,
+        })?;
+        pdus.push(pdu);
+    }
+    Ok(pdus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{carrier_profile::CarrierProfile, splitter_options::SplitterOptions, SplitSms};
+
+    #[test]
+    fn pack_hellohello() {
+        let septets = encode_gsm("hellohello").unwrap();
+        assert_eq!(to_hex(&pack_septets(&septets, 0)), "E8329BFD4697D9EC37");
+    }
+
+    #[test]
+    fn extension_characters_use_escape() {
+        assert_eq!(encode_gsm("a€").unwrap(), vec![0x61, 0x1B, 0x65]);
+        assert_eq!(
+            encode_gsm("ı").unwrap_err(),
+            PduError::UnencodableCharacter('ı')
+        );
+    }
+
+    #[test]
+    fn single_part_gsm_pdu() {
+        let result = SplitSms::default().split(String::from("hellohello"));
+        let pdus = submit_pdus(&result, &PduOptions::new("+31641600986".to_string(), 0)).unwrap();
+        assert_eq!(pdus.len(), 1);
+        assert_eq!(
+            pdus[0].to_hex(),
+            "0011000B911346610089F60000A70AE8329BFD4697D9EC37"
+        );
+        assert_eq!(pdus[0].tpdu_length(), 23);
+    }
+
+    #[test]
+    fn concatenated_gsm_pdu() {
+        let result = SplitSms::default().split("a".repeat(161));
+        let pdus = submit_pdus(&result, &PduOptions::new("0812345678".to_string(), 7)).unwrap();
+        assert_eq!(pdus.len(), 2);
+        let hex = pdus[0].to_hex();
+        // UDHI set, 160 septets including the 7 septet header, concatenation IE 1 of 2
+        assert!(hex.starts_with("0051000A8180214365870000A7A0050003070201"));
+        assert_eq!(pdus[0].tpdu_length(), 153);
+        assert!(pdus[1].to_hex().contains("050003070202"));
+    }
+
+    #[test]
+    fn profile_headers() {
+        let options = |udh| PduOptions {
+            udh,
+            ..PduOptions::new("0812345678".to_string(), 0x1234)
+        };
+        // independent 160 septet parts, no header
+        let cdma = SplitSms::with_profile(Default::default(), CarrierProfile::cdma());
+        let result = cdma.split("a".repeat(200));
+        let pdus = submit_pdus(&result, &options(UdhStyle::None)).unwrap();
+        assert_eq!(pdus.len(), 2);
+        assert!(pdus[0].to_hex().starts_with("0011000A8180214365870000A7A0"));
+        assert_eq!(pdus[0].tpdu_length(), 153);
+
+        let gsm_16bit =
+            SplitSms::with_profile(Default::default(), CarrierProfile::gsm_16bit_reference());
+        let result = gsm_16bit.split("a".repeat(200));
+        let pdus = submit_pdus(&result, &options(UdhStyle::Concatenated16Bit)).unwrap();
+        assert!(pdus[0]
+            .to_hex()
+            .starts_with("0051000A8180214365870000A7A006080412340201"));
+        assert!(pdus[1].to_hex().contains("06080412340202"));
+    }
+
+    #[test]
+    fn national_shift_tables() {
+        let splitter = SplitSms::new(SplitterOptions::new(true, false));
+        let result = splitter.split(String::from("Ağaç"));
+        let error = submit_pdus(&result, &PduOptions::new("+905321234567".to_string(), 0));
+        assert_eq!(error.unwrap_err(), PduError::NationalShiftTables);
+        // without the shift tables the message goes out as UCS-2
+        let result = SplitSms::default().split(String::from("Ağaç"));
+        let pdus = submit_pdus(&result, &PduOptions::new("+905321234567".to_string(), 0)).unwrap();
+        assert!(pdus[0].to_hex().contains("0008A708"));
+    }
+
+    #[test]
+    fn ucs2_pdu() {
+        let result = SplitSms::default().split(String::from("สวัสดี"));
+        let pdus = submit_pdus(&result, &PduOptions::new("+66812345678".to_string(), 0)).unwrap();
+        assert_eq!(
+            pdus[0].to_hex(),
+            "0011000B916618325476F80008A70C0E2A0E270E310E2A0E140E35"
+        );
+    }
+
+    #[test]
+    fn too_many_parts() {
+        let result = SplitSms::default().split("a".repeat(153 * 256));
+        assert!(result.exceeds_max_parts);
+        let error = submit_pdus(&result, &PduOptions::new("+31641600986".to_string(), 0));
+        assert_eq!(error.unwrap_err(), PduError::TooManyParts(256));
+    }
+
+    #[test]
+    fn invalid_address() {
+        let result = SplitSms::default().split(String::from("hi"));
+        let error = submit_pdus(&result, &PduOptions::new("+66-81".to_string(), 0)).unwrap_err();
+        assert_eq!(error, PduError::InvalidAddress("+66-81".to_string()));
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::{Add, AddAssign},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{character_set::CharacterSet, SplitSmsResult};
+
+/// An amount of money in millionths of the currency unit, so totals add up exactly. Read and
+/// written as a number of currency units, like `0.0075`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(try_from = "f64", into = "f64")]
+pub struct Amount(u64);
+
+impl Amount {
+    pub const MICROS_PER_UNIT: u64 = 1_000_000;
+
+    pub fn from_micros(micros: u64) -> Amount {
+        Amount(micros)
+    }
+
+    pub fn micros(self) -> u64 {
+        self.0
+    }
+
+    /// The amount of `parts` parts at this price.
+    pub fn times(self, parts: usize) -> Amount {
+        Amount(self.0 * parts as u64)
+    }
+}
+
+impl TryFrom<f64> for Amount {
+    type Error = String;
+
+    // prices are rounded to the nearest millionth
+    fn try_from(units: f64) -> Result<Amount, String> {
+        let micros = (units * Amount::MICROS_PER_UNIT as f64).round();
+        if !(0.0..=u64::MAX as f64).contains(&micros) {
+            return Err(std::format!("invalid amount {}", units));
+        }
+        Ok(Amount(micros as u64))
+    }
+}
+
+impl From<Amount> for f64 {
+    fn from(amount: Amount) -> f64 {
+        amount.0 as f64 / Amount::MICROS_PER_UNIT as f64
+    }
+}
+
+impl Add for Amount {
+    type Output = Amount;
+
+    fn add(self, other: Amount) -> Amount {
+        Amount(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Amount {
+    fn add_assign(&mut self, other: Amount) {
+        self.0 += other.0;
+    }
+}
+
+/// In currency units with at least two decimals, like `0.0775` or `1.50`.
+impl std::fmt::Display for Amount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let units = self.0 / Amount::MICROS_PER_UNIT;
+        let fraction = std::format!("{:06}", self.0 % Amount::MICROS_PER_UNIT);
+        let trimmed = fraction.trim_end_matches('0');
+        write!(f, "{}.{:0<2}", units, trimmed)
+    }
+}
+
+/// Price of one part to a destination. `unicode` defaults to the GSM price for destinations that
+/// do not charge by encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CountryPrice {
+    pub gsm: Amount,
+    #[serde(default)]
+    pub unicode: Option<Amount>,
+}
+
+impl CountryPrice {
+    pub fn per_part(&self, character_set: CharacterSet) -> Amount {
+        if character_set.is_gsm() {
+            return self.gsm;
+        }
+        self.unicode.unwrap_or(self.gsm)
+    }
+}
+
+/// Per part prices keyed by ISO 3166-1 alpha-2 country code, loaded from JSON or TOML:
+///
+/// ```json
+/// {
+///   "currency": "USD",
+///   "countries": { "TH": { "gsm": 0.02, "unicode": 0.03 }, "US": { "gsm": 0.0075 } },
+///   "default": { "gsm": 0.05 }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceTable {
+    pub currency: String,
+    #[serde(default)]
+    pub countries: HashMap<String, CountryPrice>,
+    /// Used for countries missing from `countries`.
+    #[serde(default)]
+    pub default: Option<CountryPrice>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PricingError {
+    Parse(String),
+    /// No price for the country and no default.
+    UnknownCountry(String),
+}
+
+impl std::fmt::Display for PricingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PricingError::Parse(e) => write!(f, "invalid price table: {}", e),
+            PricingError::UnknownCountry(country) => write!(f, "no price for country {}", country),
+        }
+    }
+}
+
+impl std::error::Error for PricingError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MessageCost {
+    pub country: String,
+    pub character_set: CharacterSet,
+    pub parts: usize,
+    pub price_per_part: Amount,
+    pub cost: Amount,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct CampaignCost {
+    pub messages: Vec<MessageCost>,
+    pub parts: usize,
+    pub cost: Amount,
+    pub cost_by_country: BTreeMap<String, Amount>,
+    pub cost_by_character_set: BTreeMap<CharacterSet, Amount>,
+}
+
+impl PriceTable {
+    pub fn from_json(data: &str) -> Result<PriceTable, PricingError> {
+        serde_json::from_str(data)
+            .map(PriceTable::normalized)
+            .map_err(|e| PricingError::Parse(e.to_string()))
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(data: &str) -> Result<PriceTable, PricingError> {
+        toml::from_str(data)
+            .map(PriceTable::normalized)
+            .map_err(|e| PricingError::Parse(e.to_string()))
+    }
+
+    // country codes are matched case-insensitively
+    fn normalized(self) -> PriceTable {
+        PriceTable {
+            countries: self
+                .countries
+                .into_iter()
+                .map(|(country, price)| (country.to_ascii_uppercase(), price))
+                .collect(),
+            ..self
+        }
+    }
+
+    pub fn country_price(&self, country: &str) -> Result<CountryPrice, PricingError> {
+        self.countries
+            .get(&country.to_ascii_uppercase())
+            .or(self.default.as_ref())
+            .copied()
+            .ok_or_else(|| PricingError::UnknownCountry(country.to_string()))
+    }
+
+    pub fn message_cost(
+        &self,
+        result: &SplitSmsResult,
+        country: &str,
+    ) -> Result<MessageCost, PricingError> {
+        let price_per_part = self.country_price(country)?.per_part(result.character_set);
+        Ok(MessageCost {
+            country: country.to_ascii_uppercase(),
+            character_set: result.character_set,
+            parts: result.parts.len(),
+            price_per_part,
+            cost: price_per_part.times(result.parts.len()),
+        })
+    }
+
+    /// Costs every message of a campaign, given as split results with their destination country.
+    pub fn campaign_cost<'a, I>(&self, messages: I) -> Result<CampaignCost, PricingError>
+    where
+        I: IntoIterator<Item = (&'a SplitSmsResult, &'a str)>,
+    {
+        let mut campaign = CampaignCost::default();
+        for (result, country) in messages {
+            let message = self.message_cost(result, country)?;
+            campaign.parts += message.parts;
+            campaign.cost += message.cost;
+            *campaign
+                .cost_by_country
+                .entry(message.country.clone())
+                .or_default() += message.cost;
+            *campaign
+                .cost_by_character_set
+                .entry(message.character_set)
+                .or_default() += message.cost;
+            campaign.messages.push(message);
+        }
+        Ok(campaign)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SplitSms;
+
+    const TABLE: &str = r#"{
+        "currency": "USD",
+        "countries": {
+            "TH": { "gsm": 0.02, "unicode": 0.03 },
+            "US": { "gsm": 0.0075 }
+        }
+    }"#;
+
+    #[test]
+    fn prices_by_encoding() {
+        let table = PriceTable::from_json(TABLE).unwrap();
+        let gsm = SplitSms::default().split("a".repeat(200));
+        let unicode = SplitSms::default().split(String::from("สวัสดี"));
+        let cost = table.message_cost(&gsm, "th").unwrap();
+        assert_eq!(cost.parts, 2);
+        assert_eq!(cost.cost, Amount::from_micros(40_000));
+        assert_eq!(
+            table.message_cost(&unicode, "TH").unwrap().cost,
+            Amount::from_micros(30_000)
+        );
+        // no separate Unicode price
+        assert_eq!(
+            table.message_cost(&unicode, "US").unwrap().cost,
+            Amount::from_micros(7_500)
+        );
+    }
+
+    #[test]
+    fn unknown_country() {
+        let mut table = PriceTable::from_json(TABLE).unwrap();
+        let result = SplitSms::default().split(String::from("hi"));
+        assert_eq!(
+            table.message_cost(&result, "FR").unwrap_err(),
+            PricingError::UnknownCountry("FR".to_string())
+        );
+        table.default = Some(CountryPrice {
+            gsm: Amount::from_micros(100_000),
+            unicode: None,
+        });
+        assert_eq!(
+            table.message_cost(&result, "FR").unwrap().cost,
+            Amount::from_micros(100_000)
+        );
+    }
+
+    #[test]
+    fn campaign_totals() {
+        let table = PriceTable::from_json(TABLE).unwrap();
+        let splitter = SplitSms::default();
+        let results = [
+            splitter.split("a".repeat(200)),
+            splitter.split(String::from("สวัสดี")),
+            splitter.split(String::from("hello")),
+        ];
+        let campaign = table
+            .campaign_cost([
+                (&results[0], "TH"),
+                (&results[1], "TH"),
+                (&results[2], "US"),
+            ])
+            .unwrap();
+        assert_eq!(campaign.parts, 4);
+        assert_eq!(campaign.cost, Amount::from_micros(77_500));
+        assert_eq!(campaign.cost_by_country["TH"], Amount::from_micros(70_000));
+        assert_eq!(
+            campaign.cost_by_character_set[&CharacterSet::Ucs2],
+            Amount::from_micros(30_000)
+        );
+    }
+
+    #[test]
+    fn exact_totals() {
+        let table = PriceTable::from_json(
+            r#"{ "currency": "USD", "countries": { "GB": { "gsm": 0.1 } } }"#,
+        )
+        .unwrap();
+        let result = SplitSms::default().split(String::from("hi"));
+        let campaign = table.campaign_cost([(&result, "GB"); 10]).unwrap();
+        // ten additions of 0.1 as f64 come to 0.9999999999999999
+        assert_eq!(campaign.cost, Amount::from_micros(Amount::MICROS_PER_UNIT));
+        assert_eq!(campaign.cost.to_string(), "1.00");
+        assert_eq!(Amount::from_micros(77_500).to_string(), "0.0775");
+        let json = serde_json::to_string(&table.country_price("GB").unwrap()).unwrap();
+        assert_eq!(json, r#"{"gsm":0.1,"unicode":null}"#);
+        assert!(Amount::try_from(-0.5).is_err());
+    }
+
+    #[test]
+    fn invalid_table() {
+        assert!(matches!(
+            PriceTable::from_json("{}"),
+            Err(PricingError::Parse(_))
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn from_toml() {
+        let table = PriceTable::from_toml(
+            r#"
+            currency = "EUR"
+            [countries.de]
+            gsm = 0.07
+            unicode = 0.09
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            table.country_price("de").unwrap().unicode,
+            Some(Amount::from_micros(90_000))
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    character_set::CharacterSet,
+    encoded_char::{encode_chars, fill, word_break},
+    gsm_splitter::GsmSplitter,
+    splitter_options::{MaxPartsPolicy, SplitMode},
+    SplitSms,
+};
+
+/// A part borrowing its text from the message. Characters outside the GSM alphabet are left as
+/// written in `content`, but counted as the space they are sent as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartRef<'a> {
+    pub content: &'a str,
+    /// Byte range of `content` in the message.
+    pub range: Range<usize>,
+    pub length: usize,
+    pub bytes: usize,
+}
+
+/// [`crate::SplitSmsResult`] with parts borrowed from the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitStrResult<'a> {
+    pub character_set: CharacterSet,
+    pub parts: Vec<PartRef<'a>>,
+    pub bytes: usize,
+    pub length: usize,
+    pub remaining_in_part: usize,
+    pub exceeds_max_parts: bool,
+}
+
+impl SplitSms {
+    /// Like [`SplitSms::split`], but the parts are slices of `message`, so no text is copied.
+    /// Part numbering adds text that is not in the message and is not applied here.
+    pub fn split_str<'a>(&self, message: &'a str) -> SplitStrResult<'a> {
+        let is_gsm = self.is_gsm(message);
+        let capacity = self.profile.capacity(is_gsm);
+        let independent = self.options.split_mode == SplitMode::Independent;
+        let multi_bytes = if independent {
+            capacity.single
+        } else {
+            capacity.multi
+        };
+
+        let mut parts = if independent {
+            self.word_ranges(message, is_gsm, capacity.single)
+        } else {
+            self.ranges(message, is_gsm, multi_bytes)
+        };
+        let bytes: usize = parts.iter().map(|p| p.bytes).sum();
+        if parts.len() > 1 && bytes <= capacity.single {
+            parts = vec![PartRef {
+                content: message,
+                range: 0..message.len(),
+                length: parts.iter().map(|p| p.length).sum(),
+                bytes,
+            }];
+        }
+        if parts.is_empty() {
+            parts.push(PartRef {
+                content: "",
+                range: 0..0,
+                length: 0,
+                bytes: 0,
+            });
+        }
+
+        let exceeds_max_parts = parts.len() > self.max_parts();
+        if exceeds_max_parts && self.options.max_parts_policy == MaxPartsPolicy::Truncate {
+            parts.truncate(self.max_parts());
+        }
+        let char_bytes = if is_gsm { 1 } else { 2 };
+        let max = if parts.len() == 1 {
+            capacity.single
+        } else {
+            multi_bytes
+        };
+        SplitStrResult {
+            character_set: self.character_set(message, is_gsm),
+            bytes: parts.iter().map(|p| p.bytes).sum(),
+            length: parts.iter().map(|p| p.length).sum(),
+            remaining_in_part: max.saturating_sub(parts[parts.len() - 1].bytes) / char_bytes,
+            exceeds_max_parts,
+            parts,
+        }
+    }
+
+    // parts cut at capacity, in a single pass over the message
+    fn ranges<'a>(&self, message: &'a str, is_gsm: bool, capacity: usize) -> Vec<PartRef<'a>> {
+        let gsm = GsmSplitter::with_profile(self.splitter_options(), self.profile.clone());
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut length = 0;
+        let mut bytes = 0;
+        for (offset, c) in message.char_indices() {
+            let cost = if is_gsm {
+                gsm.sent_septets(c)
+            } else {
+                c.len_utf16() * 2
+            };
+            if length > 0 && bytes + cost > capacity {
+                parts.push(PartRef {
+                    content: &message[start..offset],
+                    range: start..offset,
+                    length,
+                    bytes,
+                });
+                start = offset;
+                length = 0;
+                bytes = 0;
+            }
+            length += 1;
+            bytes += cost;
+        }
+        if length > 0 {
+            parts.push(PartRef {
+                content: &message[start..],
+                range: start..message.len(),
+                length,
+                bytes,
+            });
+        }
+        parts
+    }
+
+    // parts ending at word boundaries, like WordSplitter
+    fn word_ranges<'a>(&self, message: &'a str, is_gsm: bool, capacity: usize) -> Vec<PartRef<'a>> {
+        let chars = encode_chars(message, is_gsm, &self.splitter_options(), &self.profile);
+        let offset = |i: usize| chars.get(i).map_or(message.len(), |c| c.offset);
+        let mut parts = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let end = word_break(&chars, start, fill(&chars, start, capacity));
+            let range = offset(start)..offset(end);
+            parts.push(PartRef {
+                content: &message[range.clone()],
+                range,
+                length: end - start,
+                bytes: chars[start..end].iter().map(|c| c.bytes).sum(),
+            });
+            start = end;
+        }
+        parts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::splitter_options::SplitterOptions;
+    use alloc::string::String;
+
+    fn assert_same(splitter: &SplitSms, message: &str) {
+        let owned = splitter.split(String::from(message));
+        let borrowed = splitter.split_str(message);
+        assert_eq!(borrowed.character_set, owned.character_set);
+        assert_eq!(borrowed.bytes, owned.bytes);
+        assert_eq!(borrowed.length, owned.length);
+        assert_eq!(borrowed.remaining_in_part, owned.remaining_in_part);
+        assert_eq!(borrowed.exceeds_max_parts, owned.exceeds_max_parts);
+        assert_eq!(borrowed.parts.len(), owned.parts.len());
+        for (part, owned_part) in borrowed.parts.iter().zip(owned.parts.iter()) {
+            assert_eq!(part.content, owned_part.content);
+            assert_eq!(part.bytes, owned_part.bytes);
+            assert_eq!(part.length, owned_part.length);
+            assert_eq!(&message[part.range.clone()], part.content);
+        }
+    }
+
+    #[test]
+    fn matches_owned_split() {
+        let messages = [
+            String::new(),
+            String::from("Hello World!"),
+            "a".repeat(160),
+            "a".repeat(161),
+            "a".repeat(152) + "{" + &"b".repeat(10),
+            "€".repeat(200),
+            "ก".repeat(70),
+            "ก".repeat(71),
+            "ก".repeat(66) + "😀" + &"ข".repeat(10),
+            "😀".repeat(100),
+            "word ".repeat(100),
+        ];
+        let independent = SplitSms::new(SplitterOptions {
+            split_mode: SplitMode::Independent,
+            ..Default::default()
+        });
+        for message in messages.iter() {
+            assert_same(&SplitSms::default(), message);
+            assert_same(&independent, message);
+        }
+    }
+
+    #[test]
+    fn ranges_point_into_message() {
+        let message = "สวัสดี ".repeat(15);
+        let result = SplitSms::default().split_str(&message);
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.parts[1].range.end, message.len());
+        assert_eq!(result.parts[0].range.end, result.parts[1].range.start);
+        assert!(std::ptr::eq(
+            result.parts[0].content,
+            &message[..result.parts[0].range.end]
+        ));
+    }
+
+    #[test]
+    fn truncates_parts() {
+        let splitter = SplitSms::new(SplitterOptions {
+            max_parts: Some(2),
+            max_parts_policy: MaxPartsPolicy::Truncate,
+            ..Default::default()
+        });
+        assert_same(&splitter, &"a".repeat(400));
+    }
+}
@@ -0,0 +1,38 @@
+use alloc::string::String;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SplitError {
+    /// The message needs `parts` parts, more than `max_parts` allows.
+    TooManyParts { parts: usize, max_parts: usize },
+    /// A GSM part held a code that is not a character.
+    InvalidCharacter(u32),
+    /// A Unicode part did not decode as UTF-16.
+    InvalidUtf16,
+    /// The options or carrier profile cannot be split with.
+    InvalidOptions(String),
+    /// A template placeholder has no value or constraint.
+    UnknownPlaceholder(String),
+    Serialization(String),
+}
+
+impl core::fmt::Display for SplitError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SplitError::TooManyParts { parts, max_parts } => {
+                write!(
+                    f,
+                    "message needs {} parts, the limit is {}",
+                    parts, max_parts
+                )
+            }
+            SplitError::InvalidCharacter(code) => write!(f, "invalid character code {:#x}", code),
+            SplitError::InvalidUtf16 => write!(f, "part is not valid UTF-16"),
+            SplitError::InvalidOptions(e) => write!(f, "invalid options: {}", e),
+            SplitError::UnknownPlaceholder(name) => write!(f, "unknown placeholder {{{}}}", name),
+            SplitError::Serialization(e) => write!(f, "cannot serialize result: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SplitError {}
@@ -1,9 +1,78 @@
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug,Default,Serialize, Deserialize)]
+use crate::part_numbering::PartNumbering;
+
+/// Most parts a concatenated message can have, the UDH part counter is a single octet.
+pub const MAX_CONCATENATED_PARTS: usize = 255;
+
+/// What to do when a message needs more parts than `max_parts`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum MaxPartsPolicy {
+    /// `try_split` returns an error.
+    #[default]
+    Error,
+    /// Parts past the limit are dropped.
+    Truncate,
+    /// The result is returned whole with `exceeds_max_parts` set.
+    Flag,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SplitMode {
+    /// Parts carry a concatenation header and are cut exactly at capacity.
+    #[default]
+    Concatenated,
+    /// Parts are independent messages using the full single message capacity, cut at word
+    /// boundaries where possible.
+    Independent,
+}
+
+/// What a forced GSM encoding sends for characters outside the alphabet.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Replacement {
+    /// The closest GSM character, `'` for a curly quote or `a` for `á`, and `?` when there is
+    /// none.
+    #[default]
+    Lookalike,
+    /// Always this character, a space when it is not in the GSM alphabet itself.
+    Char(char),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Encoding {
+    /// GSM when every character allows it, UCS-2 otherwise.
+    #[default]
+    Auto,
+    /// GSM 7-bit, replacing the characters it cannot encode.
+    Gsm(Replacement),
+    /// UCS-2 even when the message would fit in GSM.
+    Ucs2,
+}
+
+#[derive(Debug,Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SplitterOptions {
     pub support_shift_tables: bool,
     pub summary: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_parts: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_parts_policy: MaxPartsPolicy,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub split_mode: SplitMode,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub part_numbering: Option<PartNumbering>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub encoding: Encoding,
 }
 
 impl SplitterOptions {
@@ -11,6 +80,7 @@ impl SplitterOptions {
         SplitterOptions {
             support_shift_tables,
             summary,
+            ..Default::default()
         }
     }
 }
@@ -20,6 +90,11 @@ impl Clone for SplitterOptions {
         SplitterOptions {
             support_shift_tables: self.support_shift_tables,
             summary: self.summary,
+            max_parts: self.max_parts,
+            max_parts_policy: self.max_parts_policy,
+            split_mode: self.split_mode,
+            part_numbering: self.part_numbering.clone(),
+            encoding: self.encoding,
         }
     }
 }
@@ -1,3 +1,9 @@
+use alloc::{string::String, vec, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
 #[derive(Debug)]
 pub struct SplitterResult {
     pub parts: Vec<SplitterPart>,
@@ -14,6 +20,30 @@ impl SplitterResult {
             total_bytes: 0,
         }
     }
+
+    // sets the offsets and counts of parts covering `lengths` characters of the message each
+    pub(crate) fn set_offsets<I>(&mut self, message: &str, lengths: I)
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut chars = message.chars();
+        let mut offset = PartOffsets::default();
+        for (part, length) in self.parts.iter_mut().zip(lengths) {
+            let start = offset;
+            for c in chars.by_ref().take(length) {
+                offset.byte_end += c.len_utf8();
+                offset.char_end += 1;
+                offset.utf16_end += c.len_utf16();
+            }
+            part.offsets = PartOffsets {
+                byte_start: start.byte_end,
+                char_start: start.char_end,
+                utf16_start: start.utf16_end,
+                ..offset
+            };
+            part.counts = Counts::new(&message[start.byte_end..offset.byte_end], part.bytes);
+        }
+    }
 }
 
 impl Clone for SplitterResult {
@@ -26,11 +56,122 @@ impl Clone for SplitterResult {
     }
 }
 
-#[derive(Debug)]
+/// Where a part's text is in the original message, as UTF-8 byte, `char` and UTF-16 indices.
+/// Ends are exclusive. Part numbering counters are not part of the message and not covered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PartOffsets {
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+    pub utf16_start: usize,
+    pub utf16_end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum OffsetUnit {
+    Byte,
+    Char,
+    Utf16,
+}
+
+impl PartOffsets {
+    pub fn start(&self, unit: OffsetUnit) -> usize {
+        match unit {
+            OffsetUnit::Byte => self.byte_start,
+            OffsetUnit::Char => self.char_start,
+            OffsetUnit::Utf16 => self.utf16_start,
+        }
+    }
+
+    pub fn end(&self, unit: OffsetUnit) -> usize {
+        match unit {
+            OffsetUnit::Byte => self.byte_end,
+            OffsetUnit::Char => self.char_end,
+            OffsetUnit::Utf16 => self.utf16_end,
+        }
+    }
+}
+
+/// The size of the message text of a part in each unit it can be counted in, part numbering
+/// counters excluded. `encoded_units` is the size of the whole part.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Counts {
+    /// User-perceived characters, an emoji with modifiers is one.
+    pub graphemes: usize,
+    /// Unicode scalar values, Rust `char`s.
+    pub scalars: usize,
+    pub utf16_units: usize,
+    /// Septets for GSM and octets for Unicode, like `bytes`.
+    pub encoded_units: usize,
+}
+
+impl Counts {
+    pub fn new(text: &str, encoded_units: usize) -> Counts {
+        Counts {
+            graphemes: text.graphemes(true).count(),
+            scalars: text.chars().count(),
+            utf16_units: text.encode_utf16().count(),
+            encoded_units,
+        }
+    }
+}
+
+impl core::iter::Sum for Counts {
+    fn sum<I: Iterator<Item = Counts>>(counts: I) -> Self {
+        counts.fold(Counts::default(), |total, c| Counts {
+            graphemes: total.graphemes + c.graphemes,
+            scalars: total.scalars + c.scalars,
+            utf16_units: total.utf16_units + c.utf16_units,
+            encoded_units: total.encoded_units + c.encoded_units,
+        })
+    }
+}
+
+/// A cursor located in a part: `part` indexes the parts, `offset` is counted from the start of
+/// the part's text in the same unit as the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CursorPosition {
+    pub part: usize,
+    pub offset: usize,
+}
+
+/// Finds the part a cursor in the original message falls in. A cursor on a boundary belongs to
+/// the part that starts there, except at the end of the message. None past the last part.
+pub fn cursor_position(
+    parts: &[SplitterPart],
+    cursor: usize,
+    unit: OffsetUnit,
+) -> Option<CursorPosition> {
+    let last = parts.len().checked_sub(1)?;
+    parts
+        .iter()
+        .enumerate()
+        .find(|(i, p)| {
+            let end = p.offsets.end(unit);
+            cursor < end || (*i == last && cursor == end)
+        })
+        .map(|(part, p)| CursorPosition {
+            part,
+            offset: cursor.saturating_sub(p.offsets.start(unit)),
+        })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SplitterPart {
     pub content: String,
     pub length: usize,
     pub bytes: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub offsets: PartOffsets,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub counts: Counts,
 }
 impl SplitterPart {
     pub fn new(content: String, length: usize, bytes: usize) -> Self {
@@ -38,6 +179,11 @@ impl SplitterPart {
             content,
             length,
             bytes,
+            offsets: PartOffsets::default(),
+            counts: Counts {
+                encoded_units: bytes,
+                ..Counts::default()
+            },
         }
     }
 }
@@ -48,6 +194,117 @@ impl Clone for SplitterPart {
             content: self.content.clone(),
             length: self.length,
             bytes: self.bytes,
+            offsets: self.offsets,
+            counts: self.counts,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{part_numbering::PartNumbering, splitter_options::SplitterOptions, SplitSms};
+
+    #[test]
+    fn offsets_in_every_unit() {
+        let message = "😀".repeat(40) + &"ก".repeat(40);
+        let result = SplitSms::default().split(message.clone());
+        assert_eq!(result.parts.len(), 2);
+        let (first, second) = (result.parts[0].offsets, result.parts[1].offsets);
+        // 33 emoji fill 132 octets of the first part
+        assert_eq!(
+            first,
+            PartOffsets {
+                byte_start: 0,
+                byte_end: 33 * 4,
+                char_start: 0,
+                char_end: 33,
+                utf16_start: 0,
+                utf16_end: 66,
+            }
+        );
+        assert_eq!(second.byte_start, first.byte_end);
+        assert_eq!(second.byte_end, message.len());
+        assert_eq!(second.char_end, 80);
+        assert_eq!(second.utf16_end, 120);
+        assert_eq!(
+            &message[second.byte_start..second.byte_end],
+            result.parts[1].content
+        );
+    }
+
+    #[test]
+    fn offsets_skip_counters() {
+        let splitter = SplitSms::new(SplitterOptions {
+            part_numbering: Some(PartNumbering::default()),
+            ..Default::default()
+        });
+        let result = splitter.split("a".repeat(200));
+        assert_eq!(result.parts[0].offsets.char_end, 147);
+        assert_eq!(result.parts[1].offsets.char_start, 147);
+        assert_eq!(result.parts[1].offsets.char_end, 200);
+    }
+
+    #[test]
+    fn counts_in_every_unit() {
+        // a family emoji is one grapheme of 5 scalars
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let result = SplitSms::default().split(String::from(family) + "é");
+        assert_eq!(
+            result.counts,
+            Counts {
+                graphemes: 2,
+                scalars: 6,
+                utf16_units: 9,
+                encoded_units: 18,
+            }
+        );
+        assert_eq!(result.parts[0].counts, result.counts);
+
+        let result = SplitSms::default().split("a".repeat(150) + &"{".repeat(10));
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.counts.graphemes, 160);
+        assert_eq!(result.counts.encoded_units, 170);
+        assert_eq!(result.parts[1].counts.scalars, result.parts[1].length);
+
+        // counters are not message text
+        let splitter = SplitSms::new(SplitterOptions {
+            part_numbering: Some(PartNumbering::default()),
+            ..Default::default()
+        });
+        let result = splitter.split("a".repeat(200));
+        assert_eq!(result.counts.scalars, 200);
+        assert_eq!(result.counts.encoded_units, result.bytes);
+    }
+
+    #[test]
+    fn cursor_positions() {
+        let message = "€".repeat(100) + &"a".repeat(100);
+        let result = SplitSms::default().split(message.clone());
+        assert_eq!(result.parts[0].offsets.char_end, 76);
+        assert_eq!(
+            result.cursor_position(10, OffsetUnit::Char),
+            Some(CursorPosition {
+                part: 0,
+                offset: 10
+            })
+        );
+        // on a boundary the cursor types into the next part
+        assert_eq!(
+            result.cursor_position(76, OffsetUnit::Char),
+            Some(CursorPosition { part: 1, offset: 0 })
+        );
+        assert_eq!(
+            result.cursor_position(76 * 3 + 1, OffsetUnit::Byte),
+            Some(CursorPosition { part: 1, offset: 1 })
+        );
+        assert_eq!(
+            result.cursor_position(200, OffsetUnit::Utf16),
+            Some(CursorPosition {
+                part: 1,
+                offset: 124
+            })
+        );
+        assert_eq!(result.cursor_position(201, OffsetUnit::Char), None);
+    }
+}
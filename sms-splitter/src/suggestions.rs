@@ -0,0 +1,305 @@
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::cmp::Reverse;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{character_set::CharacterSet, gsm_validator::GsmValidator, SplitSms, SplitSmsResult};
+
+// characters outside the GSM alphabet and what they can be written as
+const LOOKALIKES: &[(char, &str)] = &[
+    ('\u{a0}', " "),
+    ('\u{ab}', "\""),
+    ('\u{b4}', "'"),
+    ('\u{bb}', "\""),
+    ('á', "a"),
+    ('â', "a"),
+    ('ã', "a"),
+    ('ç', "Ç"),
+    ('ê', "e"),
+    ('ë', "e"),
+    ('í', "i"),
+    ('î', "i"),
+    ('ï', "i"),
+    ('ó', "o"),
+    ('ô', "o"),
+    ('õ', "o"),
+    ('ú', "u"),
+    ('û', "u"),
+    ('\u{2002}', " "),
+    ('\u{2003}', " "),
+    ('\u{2009}', " "),
+    ('\u{2010}', "-"),
+    ('\u{2011}', "-"),
+    ('\u{2012}', "-"),
+    ('\u{2013}', "-"),
+    ('\u{2014}', "-"),
+    ('\u{2018}', "'"),
+    ('\u{2019}', "'"),
+    ('\u{201a}', ","),
+    ('\u{201c}', "\""),
+    ('\u{201d}', "\""),
+    ('\u{201e}', "\""),
+    ('\u{2022}', "-"),
+    ('\u{2026}', "..."),
+    ('\u{2032}', "'"),
+    ('\u{2212}', "-"),
+];
+
+// the GSM text a character outside the alphabet can be written as
+pub(crate) fn lookalike(c: char) -> Option<&'static str> {
+    LOOKALIKES
+        .iter()
+        .find(|(from, _)| *from == c)
+        .map(|(_, to)| *to)
+}
+
+// extension table characters and their replacements in the default alphabet
+const EXTENSION_LOOKALIKES: &[(char, &str)] = &[
+    ('\x0c', ""),
+    ('[', "("),
+    ('\\', "/"),
+    (']', ")"),
+    ('{', "("),
+    ('|', "/"),
+    ('}', ")"),
+    ('~', "-"),
+];
+
+// characters that are not displayed but still counted
+const INVISIBLE: &[char] = &[
+    '\u{ad}', '\u{200b}', '\u{200c}', '\u{200d}', '\u{200e}', '\u{200f}', '\u{2060}', '\u{feff}',
+];
+
+/// A change to the message text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Edit {
+    /// Replaces every occurrence of each character, an empty replacement removes it.
+    Replace(Vec<(char, &'static str)>),
+    /// Removes zero-width and other invisible characters.
+    StripInvisible,
+    /// Removes this many characters from the end.
+    TrimEnd(usize),
+}
+
+impl Edit {
+    pub fn apply(&self, message: &str) -> String {
+        match self {
+            Edit::Replace(replacements) => message
+                .chars()
+                .map(|c| {
+                    replacements
+                        .iter()
+                        .find(|(from, _)| *from == c)
+                        .map_or_else(|| c.to_string(), |(_, to)| to.to_string())
+                })
+                .collect(),
+            Edit::StripInvisible => message.chars().filter(|c| !INVISIBLE.contains(c)).collect(),
+            Edit::TrimEnd(count) => {
+                let keep = message.chars().count().saturating_sub(*count);
+                message.chars().take(keep).collect()
+            }
+        }
+    }
+}
+
+/// An edit that saves parts, with the split of the edited message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Suggestion {
+    pub edit: Edit,
+    /// Characters of the message the edit replaces or removes.
+    pub characters: usize,
+    pub parts_saved: usize,
+    pub parts: usize,
+    pub character_set: CharacterSet,
+}
+
+impl SplitSms {
+    /// Edits of `message` that need fewer parts than `result`, its split, ranked by parts saved
+    /// then by characters changed. Trimming is the fewest characters that save one part.
+    pub fn suggest_edits(&self, message: &str, result: &SplitSmsResult) -> Vec<Suggestion> {
+        let parts = result.parts.len();
+        let mut chars: Vec<char> = message.chars().collect();
+        chars.sort_unstable();
+        chars.dedup();
+
+        let mut edits = Vec::new();
+        let mut all_lookalikes = Vec::new();
+        for c in chars.iter().copied() {
+            if self.validate_message(c.encode_utf8(&mut [0; 4])) {
+                continue;
+            }
+            // characters without a lookalike are content, only trimming may remove them
+            let Some(to) = lookalike(c) else {
+                continue;
+            };
+            all_lookalikes.push((c, to));
+            edits.push(Edit::Replace(vec![(c, to)]));
+        }
+        if all_lookalikes.len() > 1 {
+            edits.push(Edit::Replace(all_lookalikes));
+        }
+        if chars.iter().any(|c| INVISIBLE.contains(c)) {
+            edits.push(Edit::StripInvisible);
+        }
+        let validator = GsmValidator::new();
+        let extension: Vec<_> = EXTENSION_LOOKALIKES
+            .iter()
+            .filter(|(from, _)| {
+                chars.binary_search(from).is_ok()
+                    && validator.validate_extended_character(*from as u16)
+            })
+            .copied()
+            .collect();
+        if extension.len() > 1 {
+            edits.extend(
+                extension
+                    .iter()
+                    .map(|replacement| Edit::Replace(vec![*replacement])),
+            );
+        }
+        if !extension.is_empty() {
+            edits.push(Edit::Replace(extension));
+        }
+        if let Some(count) = self.trim_to_save_part(message, parts) {
+            edits.push(Edit::TrimEnd(count));
+        }
+
+        let mut suggestions: Vec<Suggestion> = edits
+            .into_iter()
+            .filter_map(|edit| {
+                let split = self.split(edit.apply(message));
+                let parts_saved = parts.checked_sub(split.parts.len()).filter(|s| *s > 0)?;
+                Some(Suggestion {
+                    characters: changed(message, &edit),
+                    parts_saved,
+                    parts: split.parts.len(),
+                    character_set: split.character_set,
+                    edit,
+                })
+            })
+            .collect();
+        suggestions.sort_by_key(|s| (Reverse(s.parts_saved), s.characters));
+        suggestions
+    }
+
+    // fewest characters to remove from the end for fewer than `parts` parts
+    fn trim_to_save_part(&self, message: &str, parts: usize) -> Option<usize> {
+        if parts <= 1 {
+            return None;
+        }
+        let chars: Vec<char> = message.chars().collect();
+        let saves = |count: usize| {
+            let kept: String = chars[..chars.len() - count].iter().collect();
+            self.split(kept).parts.len() < parts
+        };
+        // a shorter message never takes more parts, and an empty one takes one
+        let (mut low, mut high) = (1, chars.len());
+        while low < high {
+            let middle = (low + high) / 2;
+            if saves(middle) {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+        Some(low)
+    }
+}
+
+fn changed(message: &str, edit: &Edit) -> usize {
+    match edit {
+        Edit::Replace(replacements) => message
+            .chars()
+            .filter(|c| replacements.iter().any(|(from, _)| from == c))
+            .count(),
+        Edit::StripInvisible => message.chars().filter(|c| INVISIBLE.contains(c)).count(),
+        Edit::TrimEnd(count) => *count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggest(message: &str) -> Vec<Suggestion> {
+        let splitter = SplitSms::default();
+        splitter.suggest_edits(message, &splitter.split(String::from(message)))
+    }
+
+    #[test]
+    fn replace_non_gsm() {
+        // two curly quotes make 143 characters Unicode, in 3 parts
+        let message = "a".repeat(70) + "\u{2019}" + &"b".repeat(70) + "\u{201c}x";
+        let suggestions = suggest(&message);
+        assert_eq!(
+            suggestions[0].edit,
+            Edit::Replace(vec![('\u{2019}', "'"), ('\u{201c}', "\"")])
+        );
+        assert_eq!(suggestions[0].characters, 2);
+        assert_eq!(suggestions[0].parts_saved, 2);
+        assert_eq!(suggestions[0].character_set, CharacterSet::Gsm7);
+        // replacing one quote alone keeps the message Unicode, trimming saves it instead
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[1].edit, Edit::TrimEnd(9));
+        assert_eq!(
+            Edit::TrimEnd(9).apply(&message).chars().count(),
+            message.chars().count() - 9
+        );
+    }
+
+    #[test]
+    fn strip_invisible() {
+        let message = "a".repeat(100) + "\u{200b}" + &"b".repeat(50);
+        let suggestions = suggest(&message);
+        assert_eq!(suggestions[0].edit, Edit::StripInvisible);
+        assert_eq!(suggestions[0].characters, 1);
+        assert_eq!(suggestions[0].parts, 1);
+        assert_eq!(
+            Edit::StripInvisible.apply(&message),
+            "a".repeat(100) + &"b".repeat(50)
+        );
+    }
+
+    #[test]
+    fn replace_extension_characters() {
+        // 150 characters taking 165 septets
+        let message = "[".repeat(10) + &"a".repeat(135) + &"~".repeat(5);
+        let suggestions = suggest(&message);
+        let edits: Vec<_> = suggestions.iter().map(|s| s.edit.clone()).collect();
+        assert_eq!(
+            edits,
+            [
+                Edit::TrimEnd(3),
+                Edit::Replace(vec![('~', "-")]),
+                Edit::Replace(vec![('[', "(")]),
+                Edit::Replace(vec![('[', "("), ('~', "-")]),
+            ]
+        );
+        assert!(suggestions.iter().all(|s| s.parts == 1));
+    }
+
+    #[test]
+    fn keep_other_scripts() {
+        let message = "Hello ".repeat(20) + " \u{e2a}\u{e27}\u{e31}\u{e2a}\u{e14}\u{e35}";
+        let suggestions = suggest(&message);
+        assert!(suggestions
+            .iter()
+            .all(|s| !matches!(s.edit, Edit::Replace(_))));
+        assert_eq!(suggestions.len(), 1);
+        assert!(matches!(suggestions[0].edit, Edit::TrimEnd(_)));
+    }
+
+    #[test]
+    fn nothing_to_save() {
+        assert!(suggest("Hello World!").is_empty());
+        assert!(suggest("").is_empty());
+    }
+}
@@ -0,0 +1,467 @@
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{character_set::CharacterSet, splitter_error::SplitError, SplitSms, SplitSmsResult};
+
+/// A piece of a template: text sent as written, or a `{name}` placeholder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Text(String),
+    Placeholder(String),
+}
+
+/// A message with `{name}` placeholders. Names are ASCII letters, digits and `_`; any other
+/// brace is text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Template {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            let after = &rest[open + 1..];
+            let name_len = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            if name_len > 0 && after[name_len..].starts_with('}') {
+                text.push_str(&rest[..open]);
+                if !text.is_empty() {
+                    segments.push(Segment::Text(core::mem::take(&mut text)));
+                }
+                segments.push(Segment::Placeholder(after[..name_len].to_string()));
+                rest = &after[name_len + 1..];
+            } else {
+                text.push_str(&rest[..open + 1]);
+                rest = after;
+            }
+        }
+        text.push_str(rest);
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Template { segments }
+    }
+
+    /// Placeholder names in order of appearance, repeats included.
+    pub fn placeholders(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Placeholder(name) => Some(name.as_str()),
+            Segment::Text(_) => None,
+        })
+    }
+
+    /// The message for one recipient, with every placeholder replaced by its value.
+    pub fn render(&self, values: &BTreeMap<String, String>) -> Result<String, SplitError> {
+        self.fill(|name| {
+            values
+                .get(name)
+                .cloned()
+                .ok_or_else(|| SplitError::UnknownPlaceholder(name.to_string()))
+        })
+    }
+
+    // the template with every placeholder replaced by `fill`
+    fn fill<F>(&self, mut fill: F) -> Result<String, SplitError>
+    where
+        F: FnMut(&str) -> Result<String, SplitError>,
+    {
+        let mut message = String::new();
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Text(text) => message.push_str(text),
+                Segment::Placeholder(name) => message.push_str(&fill(name)?),
+            }
+        }
+        Ok(message)
+    }
+}
+
+/// The characters a placeholder value may hold.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CharClass {
+    Digits,
+    /// ASCII letters and digits.
+    Alphanumeric,
+    /// Any character of the GSM alphabet, extension table included.
+    #[default]
+    Gsm,
+    /// Any character, so the message may have to be sent as Unicode.
+    Unicode,
+}
+
+impl CharClass {
+    // the character costing the most in every encoding the class allows
+    fn worst(&self) -> char {
+        match self {
+            CharClass::Digits => '0',
+            CharClass::Alphanumeric => 'a',
+            CharClass::Gsm => '€',
+            CharClass::Unicode => '😀',
+        }
+    }
+}
+
+/// The values a placeholder may take, in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Placeholder {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub min_length: usize,
+    pub max_length: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub characters: CharClass,
+}
+
+impl Placeholder {
+    pub fn new(max_length: usize, characters: CharClass) -> Placeholder {
+        Placeholder {
+            min_length: 0,
+            max_length,
+            characters,
+        }
+    }
+}
+
+/// The split of one message a template can produce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Estimate {
+    pub character_set: CharacterSet,
+    pub parts: usize,
+    pub length: usize,
+    pub bytes: usize,
+    pub exceeds_max_parts: bool,
+}
+
+impl From<SplitSmsResult> for Estimate {
+    fn from(result: SplitSmsResult) -> Self {
+        Estimate {
+            character_set: result.character_set,
+            parts: result.parts.len(),
+            length: result.length,
+            bytes: result.bytes,
+            exceeds_max_parts: result.exceeds_max_parts,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct TemplateEstimate {
+    /// Every placeholder at its minimum length, in GSM characters.
+    pub minimum: Estimate,
+    /// Every placeholder at its maximum length, in the costliest characters of its class. A
+    /// `Unicode` placeholder makes the whole message Unicode.
+    pub worst_case: Estimate,
+}
+
+/// The recipients whose messages split the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SplitGroup {
+    pub character_set: CharacterSet,
+    pub parts: usize,
+    /// Indexes into the recipients.
+    pub recipients: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CampaignSplit {
+    /// One result per recipient, in input order, or the error rendering their message.
+    pub results: Vec<Result<SplitSmsResult, SplitError>>,
+    /// Groups by character set, then part count. Recipients that failed are in none.
+    pub groups: Vec<SplitGroup>,
+    /// Recipients whose message needs more parts than the threshold.
+    pub over_threshold: Vec<usize>,
+}
+
+impl CampaignSplit {
+    /// Recipients whose message could not be rendered, with the error.
+    pub fn failures(&self) -> impl Iterator<Item = (usize, &SplitError)> {
+        self.results
+            .iter()
+            .enumerate()
+            .filter_map(|(recipient, result)| Some((recipient, result.as_ref().err()?)))
+    }
+
+    /// Parts sent over the whole campaign.
+    pub fn total_parts(&self) -> usize {
+        self.groups
+            .iter()
+            .map(|group| group.parts * group.recipients.len())
+            .sum()
+    }
+}
+
+impl SplitSms {
+    /// Renders the template for every recipient and splits the messages. A recipient missing a
+    /// placeholder value gets an error in place of their result and the others still split.
+    pub fn split_campaign(
+        &self,
+        template: &Template,
+        recipients: &[BTreeMap<String, String>],
+        part_threshold: usize,
+    ) -> CampaignSplit {
+        let results: Vec<_> = recipients
+            .iter()
+            .map(|values| Ok(self.split(template.render(values)?)))
+            .collect();
+        let mut groups: BTreeMap<(CharacterSet, usize), Vec<usize>> = BTreeMap::new();
+        let mut over_threshold = Vec::new();
+        for (recipient, result) in results.iter().enumerate() {
+            let Ok(result) = result else {
+                continue;
+            };
+            groups
+                .entry((result.character_set, result.parts.len()))
+                .or_default()
+                .push(recipient);
+            if result.parts.len() > part_threshold {
+                over_threshold.push(recipient);
+            }
+        }
+        CampaignSplit {
+            groups: groups
+                .into_iter()
+                .map(|((character_set, parts), recipients)| SplitGroup {
+                    character_set,
+                    parts,
+                    recipients,
+                })
+                .collect(),
+            results,
+            over_threshold,
+        }
+    }
+
+    /// Part counts and encodings a template can be sent with, for placeholders constrained by
+    /// `placeholders`. Every placeholder of the template needs a constraint, with a `max_length`
+    /// no longer than the carrier's parts can hold.
+    pub fn estimate_template(
+        &self,
+        template: &Template,
+        placeholders: &BTreeMap<String, Placeholder>,
+    ) -> Result<TemplateEstimate, SplitError> {
+        // longer values never fit, whatever characters they hold
+        let longest = self
+            .profile
+            .max_parts
+            .saturating_mul(self.profile.capacity(true).single);
+        let constraint = |name: &str| {
+            let placeholder = placeholders
+                .get(name)
+                .ok_or_else(|| SplitError::UnknownPlaceholder(name.to_string()))?;
+            if placeholder.min_length > placeholder.max_length {
+                return Err(SplitError::InvalidOptions(format!(
+                    "placeholder {} has a min_length over its max_length",
+                    name
+                )));
+            }
+            if placeholder.max_length > longest {
+                return Err(SplitError::InvalidOptions(format!(
+                    "placeholder {} has a max_length over {}, the most the carrier's parts hold",
+                    name, longest
+                )));
+            }
+            Ok(*placeholder)
+        };
+        let minimum = template.fill(|name| Ok("a".repeat(constraint(name)?.min_length)))?;
+        let worst_case = template.fill(|name| {
+            let placeholder = constraint(name)?;
+            Ok(placeholder
+                .characters
+                .worst()
+                .to_string()
+                .repeat(placeholder.max_length))
+        })?;
+        Ok(TemplateEstimate {
+            minimum: self.split(minimum).into(),
+            worst_case: self.split(worst_case).into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn constraints(list: &[(&str, Placeholder)]) -> BTreeMap<String, Placeholder> {
+        list.iter()
+            .map(|(name, placeholder)| (name.to_string(), *placeholder))
+            .collect()
+    }
+
+    #[test]
+    fn parse_placeholders() {
+        let template = Template::parse("Hi {first_name}, {code} is {not a name} {}{");
+        assert_eq!(
+            template.placeholders().collect::<Vec<_>>(),
+            ["first_name", "code"]
+        );
+        assert_eq!(
+            template.segments.last(),
+            Some(&Segment::Text(String::from(" is {not a name} {}{")))
+        );
+        assert_eq!(
+            Template::parse("{a}{b}").segments,
+            [
+                Segment::Placeholder(String::from("a")),
+                Segment::Placeholder(String::from("b"))
+            ]
+        );
+    }
+
+    #[test]
+    fn gsm_placeholders() {
+        // 140 characters of text
+        let template = Template::parse(&("a".repeat(130) + "Hi {name}, {code}"));
+        let estimate = SplitSms::default()
+            .estimate_template(
+                &template,
+                &constraints(&[
+                    ("name", Placeholder::new(20, CharClass::Gsm)),
+                    ("code", Placeholder::new(6, CharClass::Digits)),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(estimate.minimum.character_set, CharacterSet::Gsm7);
+        assert_eq!(estimate.minimum.parts, 1);
+        assert_eq!(estimate.minimum.length, 135);
+        // 20 extension characters take 40 septets
+        assert_eq!(estimate.worst_case.character_set, CharacterSet::Gsm7);
+        assert_eq!(estimate.worst_case.bytes, 135 + 40 + 6);
+        assert_eq!(estimate.worst_case.parts, 2);
+    }
+
+    #[test]
+    fn unicode_placeholder_forces_unicode() {
+        let template = Template::parse("Hello {name}");
+        let estimate = SplitSms::default()
+            .estimate_template(
+                &template,
+                &constraints(&[("name", Placeholder::new(40, CharClass::Unicode))]),
+            )
+            .unwrap();
+        assert_eq!(estimate.minimum.character_set, CharacterSet::Gsm7);
+        assert_eq!(estimate.minimum.parts, 1);
+        assert_eq!(estimate.worst_case.character_set, CharacterSet::Ucs2);
+        // 6 characters and 40 surrogate pairs
+        assert_eq!(estimate.worst_case.bytes, 12 + 160);
+        assert_eq!(estimate.worst_case.parts, 2);
+    }
+
+    fn values(list: &[(&str, &str)]) -> BTreeMap<String, String> {
+        list.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn render_values() {
+        let template = Template::parse("Hi {name}, {name}!");
+        assert_eq!(
+            template.render(&values(&[("name", "Ann")])),
+            Ok(String::from("Hi Ann, Ann!"))
+        );
+        assert_eq!(
+            template.render(&BTreeMap::new()),
+            Err(SplitError::UnknownPlaceholder(String::from("name")))
+        );
+    }
+
+    #[test]
+    fn campaign_groups() {
+        let template = Template::parse(&("a".repeat(150) + " {name}"));
+        let long_name = "b".repeat(20);
+        let recipients = [
+            values(&[("name", "Ann")]),
+            values(&[("name", "Søren")]),
+            values(&[("name", &long_name)]),
+            values(&[("name", "Łukasz")]),
+            values(&[("name", "Bob")]),
+        ];
+        let campaign = SplitSms::default().split_campaign(&template, &recipients, 1);
+        assert_eq!(campaign.results.len(), 5);
+        let groups: Vec<_> = campaign
+            .groups
+            .iter()
+            .map(|g| (g.character_set, g.parts, g.recipients.clone()))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                (CharacterSet::Gsm7, 1, vec![0, 1, 4]),
+                (CharacterSet::Gsm7, 2, vec![2]),
+                (CharacterSet::Ucs2, 3, vec![3])
+            ]
+        );
+        assert_eq!(campaign.over_threshold, [2, 3]);
+        assert_eq!(campaign.total_parts(), 3 + 2 + 3);
+    }
+
+    #[test]
+    fn campaign_failures() {
+        let template = Template::parse("Hi {name}");
+        let recipients = [
+            values(&[("name", "Ann")]),
+            values(&[("first_name", "Bob")]),
+            values(&[("name", "Cleo")]),
+        ];
+        let campaign = SplitSms::default().split_campaign(&template, &recipients, 1);
+        assert_eq!(
+            campaign.failures().collect::<Vec<_>>(),
+            [(1, &SplitError::UnknownPlaceholder(String::from("name")))]
+        );
+        assert_eq!(
+            campaign.results[2].as_ref().unwrap().parts[0].content,
+            "Hi Cleo"
+        );
+        assert_eq!(campaign.groups[0].recipients, [0, 2]);
+        assert_eq!(campaign.total_parts(), 2);
+    }
+
+    #[test]
+    fn invalid_constraints() {
+        let splitter = SplitSms::default();
+        let template = Template::parse("{code}");
+        assert_eq!(
+            splitter.estimate_template(&template, &BTreeMap::new()),
+            Err(SplitError::UnknownPlaceholder(String::from("code")))
+        );
+        let placeholder = Placeholder {
+            min_length: 5,
+            ..Placeholder::new(4, CharClass::Digits)
+        };
+        assert!(matches!(
+            splitter.estimate_template(&template, &constraints(&[("code", placeholder)])),
+            Err(SplitError::InvalidOptions(_))
+        ));
+        // no value this long fits in 255 parts of 160 septets
+        let placeholder = Placeholder::new(usize::MAX, CharClass::Unicode);
+        assert_eq!(
+            splitter.estimate_template(&template, &constraints(&[("code", placeholder)])),
+            Err(SplitError::InvalidOptions(String::from(
+                "placeholder code has a max_length over 40800, the most the carrier's parts hold"
+            )))
+        );
+        let placeholder = Placeholder::new(40800, CharClass::Digits);
+        let estimate = splitter
+            .estimate_template(&template, &constraints(&[("code", placeholder)]))
+            .unwrap();
+        assert!(estimate.worst_case.exceeds_max_parts);
+    }
+}
@@ -0,0 +1,216 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{splitter_error::SplitError, SplitSms, SplitSmsResult};
+
+const GSM_ELLIPSIS: &str = "...";
+
+#[derive(Debug, Clone)]
+pub struct TruncateOptions {
+    /// Number of parts the truncated text must fit in.
+    pub max_parts: usize,
+    /// Appended when the text is cut. A GSM text falls back to `...` rather than switching to
+    /// Unicode because of the ellipsis.
+    pub ellipsis: String,
+    /// Cut at the last whitespace that fits instead of mid-word, when there is one.
+    pub word_boundary: bool,
+}
+
+impl Default for TruncateOptions {
+    fn default() -> Self {
+        TruncateOptions::new(1)
+    }
+}
+
+impl TruncateOptions {
+    pub fn new(max_parts: usize) -> TruncateOptions {
+        TruncateOptions {
+            max_parts,
+            ellipsis: GSM_ELLIPSIS.to_string(),
+            word_boundary: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TruncatedSms {
+    pub text: String,
+    /// `false` when the message already fitted and was returned unchanged.
+    pub truncated: bool,
+    pub result: SplitSmsResult,
+}
+
+impl SplitSms {
+    /// Returns the longest prefix of `message` that fits in `max_parts` parts with the ellipsis
+    /// appended. Cuts only fall between grapheme clusters, so escape sequences and surrogate
+    /// pairs are never broken. Fails with [`SplitError::InvalidOptions`] when the ellipsis alone
+    /// does not fit.
+    pub fn truncate(
+        &self,
+        message: String,
+        options: &TruncateOptions,
+    ) -> Result<TruncatedSms, SplitError> {
+        let max_parts = options.max_parts.max(1);
+        let result = self.split(message.clone());
+        if result.parts.len() <= max_parts {
+            return Ok(TruncatedSms {
+                text: message,
+                truncated: false,
+                result,
+            });
+        }
+
+        // byte offsets where the text may be cut, the empty prefix included
+        let boundaries: Vec<usize> = message.grapheme_indices(true).map(|(i, _)| i).collect();
+        let fits = |end: usize| {
+            let text = self.with_ellipsis(&message[..end], &options.ellipsis);
+            self.split(text).parts.len() <= max_parts
+        };
+        if !fits(0) {
+            return Err(SplitError::InvalidOptions(format!(
+                "the ellipsis alone is over max_parts ({})",
+                max_parts
+            )));
+        }
+        // the part count only grows with the prefix, so the longest fitting one is found by
+        // bisection
+        let mut low = 0;
+        let mut high = boundaries.len();
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if fits(boundaries[middle]) {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        let mut end = boundaries[low];
+        if options.word_boundary {
+            let cut = if message[end..].starts_with(char::is_whitespace) {
+                Some(end)
+            } else {
+                message[..end].rfind(char::is_whitespace)
+            };
+            if let Some(cut) = cut {
+                let word_end = message[..cut].trim_end().len();
+                if word_end > 0 {
+                    end = word_end;
+                }
+            }
+        }
+
+        let text = self.with_ellipsis(&message[..end], &options.ellipsis);
+        Ok(TruncatedSms {
+            result: self.split(text.clone()),
+            text,
+            truncated: true,
+        })
+    }
+
+    fn with_ellipsis(&self, prefix: &str, ellipsis: &str) -> String {
+        if self.validate_message(prefix) && !self.validate_message(ellipsis) {
+            return prefix.to_string() + GSM_ELLIPSIS;
+        }
+        prefix.to_string() + ellipsis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character_set::CharacterSet;
+
+    #[test]
+    fn short_message_unchanged() {
+        let truncated = SplitSms::default()
+            .truncate("Hello".to_string(), &TruncateOptions::new(1))
+            .unwrap();
+        assert!(!truncated.truncated);
+        assert_eq!(truncated.text, "Hello");
+    }
+
+    #[test]
+    fn fills_single_part() {
+        let truncated = SplitSms::default()
+            .truncate("a".repeat(200), &TruncateOptions::new(1))
+            .unwrap();
+        assert_eq!(truncated.text, "a".repeat(157) + "...");
+        assert_eq!(truncated.result.parts.len(), 1);
+        assert_eq!(truncated.result.remaining_in_part, 0);
+    }
+
+    #[test]
+    fn fills_several_parts() {
+        let truncated = SplitSms::default()
+            .truncate("a".repeat(500), &TruncateOptions::new(2))
+            .unwrap();
+        assert_eq!(truncated.text.len(), 306);
+        assert_eq!(truncated.result.parts.len(), 2);
+    }
+
+    #[test]
+    fn never_splits_escape_sequence() {
+        // 156 septets, then a two septet character that no longer fits with the ellipsis
+        let message = "a".repeat(156) + "€€€";
+        let truncated = SplitSms::default()
+            .truncate(message, &TruncateOptions::new(1))
+            .unwrap();
+        assert_eq!(truncated.text, "a".repeat(156) + "...");
+        assert_eq!(truncated.result.character_set, CharacterSet::Gsm7);
+    }
+
+    #[test]
+    fn never_splits_graphemes() {
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let message = "ก".to_string() + &"a".repeat(61) + family + family;
+        let options = TruncateOptions {
+            ellipsis: "…".to_string(),
+            ..TruncateOptions::new(1)
+        };
+        let truncated = SplitSms::default().truncate(message, &options).unwrap();
+        assert_eq!(truncated.text, "ก".to_string() + &"a".repeat(61) + "…");
+        assert_eq!(truncated.result.character_set, CharacterSet::Ucs2);
+    }
+
+    #[test]
+    fn gsm_text_keeps_gsm_ellipsis() {
+        let options = TruncateOptions {
+            ellipsis: "…".to_string(),
+            ..TruncateOptions::new(1)
+        };
+        let truncated = SplitSms::default()
+            .truncate("a".repeat(200), &options)
+            .unwrap();
+        assert_eq!(truncated.text, "a".repeat(157) + "...");
+    }
+
+    #[test]
+    fn word_boundary() {
+        let message = "word ".repeat(40);
+        let options = TruncateOptions {
+            word_boundary: true,
+            ..TruncateOptions::new(1)
+        };
+        let truncated = SplitSms::default().truncate(message, &options).unwrap();
+        assert!(truncated.text.ends_with("word..."));
+        assert_eq!(truncated.text.len(), 154 + 3);
+    }
+
+    #[test]
+    fn ellipsis_too_long() {
+        let options = TruncateOptions {
+            ellipsis: "-".repeat(161),
+            ..TruncateOptions::new(1)
+        };
+        let message = "a".repeat(200);
+        assert_eq!(
+            SplitSms::default().truncate(message, &options).unwrap_err(),
+            SplitError::InvalidOptions("the ellipsis alone is over max_parts (1)".to_string())
+        );
+    }
+}
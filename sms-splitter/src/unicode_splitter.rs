@@ -1,15 +1,28 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
 use crate::{
+    carrier_profile::CarrierProfile,
+    splitter_error::SplitError,
     splitter_options::SplitterOptions,
     splitter_result::{SplitterPart, SplitterResult},
 };
 
 pub struct UnicodeSplitter {
     options: SplitterOptions,
+    profile: CarrierProfile,
 }
 
 impl UnicodeSplitter {
     pub fn new(options: SplitterOptions) -> UnicodeSplitter {
-        UnicodeSplitter { options }
+        UnicodeSplitter::with_profile(options, CarrierProfile::default())
+    }
+
+    /// Splits with the capacities of a carrier.
+    pub fn with_profile(options: SplitterOptions, profile: CarrierProfile) -> UnicodeSplitter {
+        UnicodeSplitter { options, profile }
     }
 
     fn is_high_surrogate(&self, code: u16) -> bool {
@@ -17,24 +30,48 @@ impl UnicodeSplitter {
     }
 
     pub fn split(&self, message: String) -> SplitterResult {
-        let original_message = message.clone();
+        self.split_lossy(&message).0
+    }
+
+    /// Like [`UnicodeSplitter::split`], but fails instead of replacing a part that does not
+    /// decode as UTF-16.
+    pub fn try_split(&self, message: String) -> Result<SplitterResult, SplitError> {
+        match self.split_lossy(&message) {
+            (result, None) => Ok(result),
+            (_, Some(error)) => Err(error),
+        }
+    }
+
+    // splits decoding parts lossily, with the first decoding error
+    pub(crate) fn split_lossy(&self, message: &str) -> (SplitterResult, Option<SplitError>) {
+        let (mut result, error) = self.split_parts(message);
+        let lengths: Vec<usize> = result.parts.iter().map(|p| p.length).collect();
+        result.set_offsets(message, lengths);
+        (result, error)
+    }
+
+    fn split_parts(&self, message: &str) -> (SplitterResult, Option<SplitError>) {
+        let original_message = message;
         let message = message.encode_utf16().collect::<Vec<u16>>();
         if message.is_empty() {
-            return SplitterResult::empty();
+            return (SplitterResult::empty(), None);
         }
+        let mut error = None;
         let mut messages: Vec<SplitterPart> = Vec::new();
         let mut length = 0;
         let mut bytes = 0;
         let mut total_bytes = 0;
         let mut total_length = 0;
         let mut part_start = 0;
+        let capacity = self.profile.capacity(false);
         let bank = |part_start: &mut usize,
                     part_end: usize,
                     bytes: &mut usize,
                     length: &mut usize,
                     total_bytes: &mut usize,
                     total_length: &mut usize,
-                    messages: &mut Vec<SplitterPart>| {
+                    messages: &mut Vec<SplitterPart>,
+                    error: &mut Option<SplitError>| {
             let mut content: Vec<u16> = Vec::new();
             if !self.options.summary {
                 if part_end > 0 {
@@ -47,7 +84,10 @@ impl UnicodeSplitter {
                     }
                 }
             }
-            let content = String::from_utf16(&content).unwrap();
+            let content = String::from_utf16(&content).unwrap_or_else(|_| {
+                error.get_or_insert(SplitError::InvalidUtf16);
+                String::from_utf16_lossy(&content)
+            });
             let msg = SplitterPart::new(content, *length, *bytes);
             messages.push(msg);
             *part_start = part_end + 1;
@@ -59,16 +99,12 @@ impl UnicodeSplitter {
         let count = message.len();
         let mut i = 0;
         while i < count {
-            let space = *'\u{0020}'
-                .to_string()
-                .encode_utf16()
-                .collect::<Vec<u16>>().first()
-                .unwrap();
+            let space = 0x20;
             let code = message.get(i).unwrap_or(&space);
             let high_surrogate = self.is_high_surrogate(*code);
             // println!("bytes: {} {} {}", bytes, code, high_surrogate);
             if high_surrogate {
-                if bytes == 132 {
+                if bytes > 0 && bytes + 4 > capacity.multi {
                     bank(
                         &mut part_start,
                         i - 1,
@@ -77,6 +113,7 @@ impl UnicodeSplitter {
                         &mut total_bytes,
                         &mut total_length,
                         &mut messages,
+                        &mut error,
                     );
                 }
                 bytes += 2;
@@ -84,7 +121,7 @@ impl UnicodeSplitter {
             }
             bytes += 2;
             length += 1;
-            if bytes == 134 {
+            if bytes >= capacity.multi {
                 bank(
                     &mut part_start,
                     i,
@@ -93,6 +130,7 @@ impl UnicodeSplitter {
                     &mut total_bytes,
                     &mut total_length,
                     &mut messages,
+                    &mut error,
                 );
             }
             i += 1;
@@ -107,32 +145,35 @@ impl UnicodeSplitter {
                 &mut total_bytes,
                 &mut total_length,
                 &mut messages,
+                &mut error,
             );
         }
 
-        if messages.get(1).is_some() && total_bytes <= 140 {
+        if messages.get(1).is_some() && total_bytes <= capacity.single {
             let mut parts = Vec::new();
             let content: String = String::from("");
             if self.options.summary {
                 parts.push(SplitterPart::new(content, total_length, total_bytes));
             } else {
                 parts.push(SplitterPart::new(
-                    original_message,
+                    original_message.to_string(),
                     total_length,
                     total_bytes,
                 ));
             }
-            return SplitterResult {
+            let result = SplitterResult {
                 parts: parts.clone(),
                 total_length,
                 total_bytes,
             };
+            return (result, error);
         }
-        SplitterResult {
+        let result = SplitterResult {
             parts: messages,
             total_length,
             total_bytes,
-        }
+        };
+        (result, error)
     }
 }
 
@@ -142,13 +183,14 @@ impl Default for UnicodeSplitter {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "serde_json"))]
 mod tests {
     use super::*;
     // import json test data from  test folder
     use std::fs::File;
     use std::io::prelude::*;
     use std::path::Path;
+    use std::println;
 
     #[test]
     fn gsm_split() {
@@ -0,0 +1,179 @@
+use alloc::{string::String, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    carrier_profile::CarrierProfile,
+    encoded_char::{encode_chars, fill, word_break},
+    splitter_options::SplitterOptions,
+    splitter_result::{SplitterPart, SplitterResult},
+};
+
+/// How much a word boundary split costs compared with cutting every part at capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EfficiencyLoss {
+    /// Parts a hard split at the same capacity needs.
+    pub hard_parts: usize,
+    /// Parts added by cutting at word boundaries.
+    pub extra_parts: usize,
+    /// Characters of capacity left empty in every part but the last.
+    pub unused_characters: usize,
+}
+
+/// Splits into independent messages without a concatenation header, so every part has the full
+/// single message capacity. Parts end after whitespace, or else punctuation, whenever the part
+/// has one; a word longer than a part is cut at capacity.
+pub struct WordSplitter {
+    options: SplitterOptions,
+    profile: CarrierProfile,
+    is_gsm: bool,
+}
+
+impl WordSplitter {
+    pub fn new(options: SplitterOptions, is_gsm: bool) -> WordSplitter {
+        WordSplitter::with_profile(options, CarrierProfile::default(), is_gsm)
+    }
+
+    pub fn with_profile(
+        options: SplitterOptions,
+        profile: CarrierProfile,
+        is_gsm: bool,
+    ) -> WordSplitter {
+        WordSplitter {
+            options,
+            profile,
+            is_gsm,
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.profile.capacity(self.is_gsm).single
+    }
+
+    fn char_bytes(&self) -> usize {
+        if self.is_gsm {
+            return 1;
+        }
+        2
+    }
+
+    pub fn split(&self, message: String) -> SplitterResult {
+        let chars = encode_chars(&message, self.is_gsm, &self.options, &self.profile);
+        if chars.is_empty() {
+            return SplitterResult::empty();
+        }
+        let mut parts = Vec::new();
+        let mut total_bytes = 0;
+        let mut start = 0;
+        while start < chars.len() {
+            let end = word_break(&chars, start, fill(&chars, start, self.capacity()));
+            let bytes: usize = chars[start..end].iter().map(|c| c.bytes).sum();
+            let mut content = String::new();
+            if !self.options.summary {
+                content = chars[start..end].iter().map(|c| c.content).collect();
+            }
+            parts.push(SplitterPart::new(content, end - start, bytes));
+            total_bytes += bytes;
+            start = end;
+        }
+        let mut result = SplitterResult {
+            parts,
+            total_length: chars.len(),
+            total_bytes,
+        };
+        let lengths: Vec<usize> = result.parts.iter().map(|p| p.length).collect();
+        result.set_offsets(&message, lengths);
+        result
+    }
+
+    pub fn efficiency_loss(&self, message: &str, parts: &[SplitterPart]) -> EfficiencyLoss {
+        let chars = encode_chars(message, self.is_gsm, &self.options, &self.profile);
+        let mut hard_parts = 0;
+        let mut start = 0;
+        while start < chars.len() {
+            start = fill(&chars, start, self.capacity());
+            hard_parts += 1;
+        }
+        let hard_parts = hard_parts.max(1);
+        let unused_characters = parts
+            .iter()
+            .take(parts.len().saturating_sub(1))
+            .map(|p| self.capacity().saturating_sub(p.bytes) / self.char_bytes())
+            .sum();
+        EfficiencyLoss {
+            hard_parts,
+            extra_parts: parts.len().saturating_sub(hard_parts),
+            unused_characters,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn contents(result: &SplitterResult) -> Vec<String> {
+        result.parts.iter().map(|p| p.content.clone()).collect()
+    }
+
+    #[test]
+    fn breaks_after_whitespace() {
+        let message = "word ".repeat(40);
+        let splitter = WordSplitter::new(SplitterOptions::default(), true);
+        let result = splitter.split(message.clone());
+        assert_eq!(
+            contents(&result),
+            vec!["word ".repeat(32), "word ".repeat(8)]
+        );
+        assert_eq!(result.total_length, 200);
+        let loss = splitter.efficiency_loss(&message, &result.parts);
+        assert_eq!(
+            loss,
+            EfficiencyLoss {
+                hard_parts: 2,
+                extra_parts: 0,
+                unused_characters: 0
+            }
+        );
+    }
+
+    #[test]
+    fn reports_extra_parts() {
+        // one 100 character word per part, where a hard split needs 2 parts
+        let message = ("a".repeat(99) + " ").repeat(3);
+        let splitter = WordSplitter::new(SplitterOptions::default(), true);
+        let result = splitter.split(message.clone());
+        assert_eq!(result.parts.len(), 3);
+        let loss = splitter.efficiency_loss(&message, &result.parts);
+        assert_eq!(loss.hard_parts, 2);
+        assert_eq!(loss.extra_parts, 1);
+        assert_eq!(loss.unused_characters, 120);
+    }
+
+    #[test]
+    fn falls_back_to_punctuation_then_hard_cut() {
+        let message = "a".repeat(150) + "," + &"b".repeat(20);
+        let splitter = WordSplitter::new(SplitterOptions::default(), true);
+        assert_eq!(
+            contents(&splitter.split(message)),
+            vec!["a".repeat(150) + ",", "b".repeat(20)]
+        );
+        let message = "a".repeat(170);
+        assert_eq!(
+            contents(&splitter.split(message)),
+            vec!["a".repeat(160), "a".repeat(10)]
+        );
+    }
+
+    #[test]
+    fn unicode_capacity() {
+        let message = "ทดสอบ ".repeat(20);
+        let splitter = WordSplitter::new(SplitterOptions::default(), false);
+        let result = splitter.split(message);
+        assert_eq!(result.parts[0].content, "ทดสอบ ".repeat(11));
+        assert_eq!(result.parts[0].bytes, 132);
+    }
+}